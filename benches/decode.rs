@@ -0,0 +1,137 @@
+//! Throughput of decoding a long stream of small structs,
+//! as written by a Go producer sending one record per message.
+//!
+//! Each case runs on streams of 10k and 1M messages, see the README for comparing runs.
+
+#[macro_use] extern crate criterion;
+#[macro_use] extern crate serde_derive;
+extern crate gob;
+extern crate serde;
+extern crate tempdir;
+
+#[path = "../tests/utils/wire.rs"]
+#[allow(dead_code)]
+mod wire;
+
+use std::fs::File;
+use std::hint::black_box;
+use std::io::Write;
+use criterion::{BenchmarkId,Criterion,Throughput};
+use gob::{Deserializer,Projection};
+use tempdir::TempDir;
+use wire::Stream;
+
+/// Numbers of messages per stream
+const SIZES: [usize; 2] = [10_000, 1_000_000];
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Trade {
+    #[serde(rename="Id")] id: u64,
+    #[serde(rename="Symbol")] symbol: String,
+    #[serde(rename="Price")] price: i64,
+    #[serde(rename="Qty")] qty: i64,
+    #[serde(rename="Buy")] buy: bool,
+    #[serde(rename="Venue")] venue: Venue,
+    #[serde(rename="Tags")] tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Venue {
+    #[serde(rename="Name")] name: String,
+    #[serde(rename="Region")] region: String,
+}
+
+#[derive(Deserialize)]
+struct TradeId {
+    #[serde(rename="Id")] id: u64,
+}
+
+fn trades(messages: usize) -> Vec<u8> {
+    let mut stream = Stream::new()
+        .structure(65, "Venue", &[("Name", 6), ("Region", 6)])
+        .slice(66, "[]string", 6)
+        .structure(67, "Trade", &[("Id", 3), ("Symbol", 6), ("Price", 2), ("Qty", 2), ("Buy", 1), ("Venue", 65), ("Tags", 66)]);
+
+    for i in 0..messages as u64 {
+        let mut trade = Vec::new();
+        wire::uint(&mut trade, 1);
+        wire::uint(&mut trade, i);
+        wire::uint(&mut trade, 1);
+        wire::string(&mut trade, "GOOG");
+        wire::uint(&mut trade, 1);
+        wire::int(&mut trade, 120_000 + i as i64);
+        wire::uint(&mut trade, 1);
+        wire::int(&mut trade, 100);
+        wire::uint(&mut trade, 1);
+        wire::uint(&mut trade, 1);
+        wire::uint(&mut trade, 1);
+        wire::uint(&mut trade, 1);
+        wire::string(&mut trade, "NASDAQ");
+        wire::uint(&mut trade, 1);
+        wire::string(&mut trade, "us-east");
+        wire::uint(&mut trade, 0);
+        wire::uint(&mut trade, 1);
+        wire::uint(&mut trade, 2);
+        wire::string(&mut trade, "block");
+        wire::string(&mut trade, "dark");
+        wire::uint(&mut trade, 0);
+        stream = stream.value(67, &trade);
+    }
+
+    stream.bytes
+}
+
+fn decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode");
+    let dir = TempDir::new("gob-bench").unwrap();
+    let projection = Projection::new(["Id"]).unwrap();
+
+    for &messages in &SIZES {
+        let bytes = trades(messages);
+        group.throughput(Throughput::Elements(messages as u64));
+        // A million messages take long enough that fewer samples give stable results
+        group.sample_size(if messages < 1_000_000 { 100 } else { 10 });
+
+        group.bench_with_input(BenchmarkId::new("structs", messages), &bytes, |b, bytes| b.iter(|| {
+            for trade in Deserializer::new(bytes.as_slice()).into_iter::<Trade>() {
+                black_box(trade.unwrap());
+            }
+        }));
+
+        group.bench_with_input(BenchmarkId::new("structs_from_slice", messages), &bytes, |b, bytes| b.iter(|| {
+            for trade in Deserializer::from_slice(bytes).into_iter::<Trade>() {
+                black_box(trade.unwrap());
+            }
+        }));
+
+        group.bench_with_input(BenchmarkId::new("structs_parallel", messages), &bytes, |b, bytes| b.iter(|| {
+            for trade in Deserializer::new(bytes.as_slice()).into_parallel_iter::<Trade>(4) {
+                black_box(trade.unwrap());
+            }
+        }));
+
+        // An unbuffered file, as users often pass it
+        let path = dir.path().join(format!("trades-{}.gob", messages));
+        File::create(&path).unwrap().write_all(&bytes).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("structs_from_file", messages), &path, |b, path| b.iter(|| {
+            for trade in Deserializer::new(File::open(path).unwrap()).into_iter::<Trade>() {
+                black_box(trade.unwrap());
+            }
+        }));
+
+        group.bench_with_input(BenchmarkId::new("projected", messages), &bytes, |b, bytes| b.iter(|| {
+            let mut de = Deserializer::new(bytes.as_slice());
+            for _ in 0..messages {
+                black_box(de.deserialize_projected::<TradeId>(&projection).unwrap().id);
+            }
+        }));
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, decode);
+criterion_main!(benches);
@@ -0,0 +1,117 @@
+//! Framing of gob streams for `tokio_util::codec`.
+//!
+//! Requires the `codec` feature.
+
+use std::marker::PhantomData;
+use bytes::BytesMut;
+use serde::de::DeserializeOwned;
+use tokio_util::codec::Decoder;
+use de::{DeserializerOptions,FrameDecoder};
+use errors::*;
+use types::TypeMap;
+
+/// Decodes the values of a gob stream from frames of bytes,
+/// e.g. with `FramedRead` on a TCP connection.
+///
+/// A codec belongs to a single stream: the types defined by earlier messages
+/// are kept and used to decode later values.
+/// Values whose messages have only arrived in part are left in the buffer
+/// until the rest of them arrives.
+///
+/// Only decoding is supported, as gob values can't be encoded yet.
+///
+/// ```no_run
+/// # #[macro_use] extern crate serde_derive;
+/// # extern crate gob;
+/// # extern crate tokio;
+/// # extern crate tokio_util;
+/// # use tokio::net::TcpStream;
+/// # use tokio_util::codec::FramedRead;
+/// # use gob::codec::GobCodec;
+/// #[derive(Deserialize)]
+/// struct Trade {
+///     Price: f64,
+/// }
+///
+/// # fn run(stream: TcpStream) {
+/// let trades = FramedRead::new(stream, GobCodec::<Trade>::new());
+/// # }
+/// # fn main() {}
+/// ```
+#[derive(Debug)]
+pub struct GobCodec<T> {
+    frames: FrameDecoder,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> GobCodec<T> {
+    pub fn new() -> Self {
+        Self::with_options(DeserializerOptions::default())
+    }
+
+    pub fn with_options(options: DeserializerOptions) -> Self {
+        GobCodec {
+            frames: FrameDecoder::new(options),
+            marker: PhantomData,
+        }
+    }
+
+    pub fn options(&self) -> &DeserializerOptions {
+        &self.frames.options
+    }
+
+    /// Returns a snapshot of the types defined so far
+    pub fn type_map(&self) -> TypeMap {
+        self.frames.types.clone()
+    }
+
+    /// Replaces the known types, like `Deserializer::set_type_map`
+    pub fn set_type_map(&mut self, types: TypeMap) {
+        self.frames.types = types;
+    }
+
+    /// Number of bytes decoded so far
+    pub fn offset(&self) -> u64 {
+        self.frames.offset
+    }
+}
+
+impl<T> Default for GobCodec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for GobCodec<T> {
+    fn clone(&self) -> Self {
+        GobCodec {
+            frames: self.frames.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: DeserializeOwned> Decoder for GobCodec<T> {
+    type Item = T;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<T>> {
+        match self.frames.frame_len(src)? {
+            Some(len) => {
+                let frame = src.split_to(len);
+                self.frames.decode(&frame)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<T>> {
+        if let Some(value) = self.decode(src)? {
+            return Ok(Some(value));
+        }
+
+        // Register trailing definitions, or fail on a partial message
+        let rest = src.split();
+        self.frames.decode(&rest)
+    }
+}
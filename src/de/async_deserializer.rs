@@ -0,0 +1,173 @@
+use std::future::Future;
+use std::io;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context,Poll,ready};
+use serde::de::DeserializeOwned;
+use tokio::io::{AsyncRead,ReadBuf};
+use errors::*;
+use types::TypeMap;
+use super::{DeserializerOptions,FrameDecoder,FrameBuffer};
+
+/// Size of the chunks read from the underlying reader
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Decodes a gob stream from a tokio `AsyncRead`, e.g. a TCP connection.
+///
+/// Messages are read into a buffer until a whole value has arrived,
+/// which is then decoded with the same machinery as `Deserializer`.
+/// Only the buffering is asynchronous, so a value is never decoded more than once.
+///
+/// Everything read so far is kept by the deserializer rather than by the future
+/// returned from [`next`], so that future can be dropped at any time,
+/// e.g. in a `select!`, without losing data.
+/// The next call to `next` picks up where the dropped one stopped.
+///
+/// Requires the `tokio` feature.
+///
+/// ```no_run
+/// # #[macro_use] extern crate serde_derive;
+/// # extern crate gob;
+/// # extern crate tokio;
+/// # use tokio::net::TcpStream;
+/// #[derive(Deserialize)]
+/// struct Trade {
+///     Price: f64,
+/// }
+///
+/// # fn run(stream: TcpStream) -> gob::Result<()> {
+/// # let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+/// let mut de = gob::AsyncDeserializer::new(stream);
+///
+/// while let Some(trade) = runtime.block_on(de.next::<Trade>())? {
+///     println!("{}", trade.Price);
+/// }
+/// # Ok(())
+/// # }
+/// # fn main() {}
+/// ```
+///
+/// [`next`]: #method.next
+pub struct AsyncDeserializer<R> {
+    reader: R,
+    /// Bytes read but not decoded yet, starting at a message boundary
+    buf: FrameBuffer,
+    eof: bool,
+    frames: FrameDecoder,
+}
+
+impl<R: AsyncRead + Unpin> AsyncDeserializer<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_options(reader, DeserializerOptions::default())
+    }
+
+    pub fn with_options(reader: R, options: DeserializerOptions) -> Self {
+        AsyncDeserializer {
+            reader,
+            buf: FrameBuffer::new(),
+            eof: false,
+            frames: FrameDecoder::new(options),
+        }
+    }
+
+    pub fn options(&self) -> &DeserializerOptions {
+        &self.frames.options
+    }
+
+    /// Returns a snapshot of the types defined so far
+    pub fn type_map(&self) -> TypeMap {
+        self.frames.types.clone()
+    }
+
+    /// Replaces the known types, like `Deserializer::set_type_map`
+    pub fn set_type_map(&mut self, types: TypeMap) {
+        self.frames.types = types;
+    }
+
+    /// Offset of the next message that hasn't been decoded yet
+    pub fn offset(&self) -> u64 {
+        self.frames.offset
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Returns the reader, along with any bytes read from it that haven't been decoded yet
+    pub fn into_inner(self) -> (R, Vec<u8>) {
+        (self.reader, self.buf.into_vec())
+    }
+
+    /// Returns a future resolving to the next value,
+    /// or to `None` if the stream ends at a message boundary.
+    ///
+    /// The future is cancellation safe.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next<'a, T: DeserializeOwned>(&'a mut self) -> Next<'a, R, T> {
+        Next {
+            de: self,
+            marker: PhantomData,
+        }
+    }
+
+    /// Polls for the next value, reading from the underlying reader until it has arrived completely
+    pub fn poll_next<T: DeserializeOwned>(&mut self, cx: &mut Context) -> Poll<Result<Option<T>>> {
+        loop {
+            let len = match self.frames.frame_len(self.buf.pending()) {
+                Ok(len) => len,
+                Err(err) => return Poll::Ready(Err(err)),
+            };
+
+            if let Some(len) = len {
+                return Poll::Ready(self.decode(len));
+            }
+
+            if self.eof {
+                // Decode whatever is left, to register trailing definitions or report a partial message
+                let len = self.buf.len();
+                return Poll::Ready(self.decode(len));
+            }
+
+            if let Err(err) = ready!(self.poll_fill(cx)) {
+                return Poll::Ready(Err(err.into()));
+            }
+        }
+    }
+
+    /// Reads the next chunk of the stream into the buffer
+    fn poll_fill(&mut self, cx: &mut Context) -> Poll<io::Result<()>> {
+        let mut chunk = [0; CHUNK_SIZE];
+        let mut read_buf = ReadBuf::new(&mut chunk);
+
+        ready!(Pin::new(&mut self.reader).poll_read(cx, &mut read_buf))?;
+
+        let filled = read_buf.filled();
+        self.eof = filled.is_empty();
+        self.buf.extend(filled);
+
+        Poll::Ready(Ok(()))
+    }
+
+    /// Decodes the first `len` buffered bytes, which hold at most one value
+    fn decode<T: DeserializeOwned>(&mut self, len: usize) -> Result<Option<T>> {
+        let value = self.frames.decode(&self.buf.pending()[..len]);
+        self.buf.consume(len);
+        value
+    }
+}
+
+/// Future returned by [`AsyncDeserializer::next`](struct.AsyncDeserializer.html#method.next)
+pub struct Next<'a, R: 'a, T> {
+    de: &'a mut AsyncDeserializer<R>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, R, T> Future for Next<'a, R, T>
+    where R: AsyncRead + Unpin, T: DeserializeOwned
+{
+    type Output = Result<Option<T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        self.get_mut().de.poll_next(cx)
+    }
+}
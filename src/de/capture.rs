@@ -0,0 +1,56 @@
+use std::collections::BTreeSet;
+use errors::*;
+use types::{TypeId,TypeDef};
+use write_gob::WriteGob;
+use super::{Deserializer,Input};
+
+impl<'de, R: Input<'de>> Deserializer<R> {
+    /// Reads past a value of the given type and returns it as a self-contained gob stream:
+    /// the definitions of all custom types it needs, followed by the value itself.
+    pub(super) fn capture_value(&mut self, type_def: &TypeDef) -> Result<Vec<u8>> {
+        let mut type_ids = Vec::new();
+
+        self.reader.start_recording();
+        let result = self.skip_value(type_def, &mut type_ids);
+        let value = self.reader.stop_recording();
+        result?;
+
+        type_ids.push(type_def.id());
+
+        let mut stream = Vec::new();
+
+        for type_id in self.type_closure(type_ids) {
+            if let Some(TypeDef::Custom(ref wire_type)) = self.types.get(type_id) {
+                let mut message = Vec::new();
+                message.write_gob_type_id(-type_id)?;
+                wire_type.encode(&mut message)?;
+                stream.write_gob_message(&message)?;
+            }
+        }
+
+        let mut message = Vec::new();
+        message.write_gob_type_id(type_def.id())?;
+        if !type_def.is_struct() {
+            message.write_gob_u64(0)?;
+        }
+        message.extend(value);
+        stream.write_gob_message(&message)?;
+
+        Ok(stream)
+    }
+
+    /// Returns the given custom types and all custom types they refer to, ordered by id
+    fn type_closure(&self, mut pending: Vec<TypeId>) -> BTreeSet<TypeId> {
+        let mut closure = BTreeSet::new();
+
+        while let Some(type_id) = pending.pop() {
+            if let Some(TypeDef::Custom(ref wire_type)) = self.types.get(type_id) {
+                if closure.insert(type_id) {
+                    pending.extend(wire_type.references().into_iter().map(|(_, id)| id));
+                }
+            }
+        }
+
+        closure
+    }
+}
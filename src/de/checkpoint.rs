@@ -0,0 +1,78 @@
+use std::io::{Seek};
+use errors::*;
+use types::TypeMap;
+use super::{Deserializer,DeserializerOptions,Input};
+
+/// The position of a deserializer between two values, along with the types known at that point.
+///
+/// A checkpoint can be serialized with serde and later passed to
+/// [`Deserializer::resume`] to continue decoding the same stream
+/// without reading it from the start again.
+///
+/// ```no_run
+/// # extern crate gob;
+/// # use std::fs::File;
+/// # fn main() -> gob::Result<()> {
+/// let mut de = gob::Deserializer::new(File::open("trades.gob")?);
+/// let first: u64 = de.deserialize()?;
+/// let checkpoint = de.checkpoint();
+///
+/// // ... later, possibly in another process
+/// let mut de = gob::Deserializer::resume(File::open("trades.gob")?, checkpoint)?;
+/// let second: u64 = de.deserialize()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Deserializer::resume`]: struct.Deserializer.html#method.resume
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub struct Checkpoint {
+    offset: u64,
+    messages: u64,
+    types: TypeMap,
+}
+
+impl Checkpoint {
+    /// Offset of the message boundary at which decoding resumes
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// The types defined before the checkpoint
+    pub fn type_map(&self) -> &TypeMap {
+        &self.types
+    }
+}
+
+impl<'de, R: Input<'de>> Deserializer<R> {
+    /// Takes a checkpoint before the next value.
+    ///
+    /// If the header of the next value has been read already, e.g. by `peek_type`,
+    /// the checkpoint still lies before that value.
+    /// Checkpoints taken after a decoding error are meaningless.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            offset: self.boundary(),
+            messages: self.messages,
+            types: self.type_map(),
+        }
+    }
+}
+
+impl<'de, R: Input<'de> + Seek> Deserializer<R> {
+    /// Creates a deserializer continuing from a checkpoint taken on the same stream.
+    /// The reader is moved to the checkpoint's offset, and offsets reported
+    /// by the deserializer keep counting from the start of the stream.
+    pub fn resume(reader: R, checkpoint: Checkpoint) -> Result<Self> {
+        Self::resume_with_options(reader, checkpoint, DeserializerOptions::default())
+    }
+
+    pub fn resume_with_options(reader: R, checkpoint: Checkpoint, options: DeserializerOptions) -> Result<Self> {
+        let mut de = Deserializer::with_options(reader, options);
+        de.reader.seek(checkpoint.offset)?;
+        de.types = checkpoint.types;
+        de.messages = checkpoint.messages;
+
+        Ok(de)
+    }
+}
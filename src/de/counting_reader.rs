@@ -0,0 +1,142 @@
+use std::cmp;
+use std::io::{self,Read,BufRead,Seek,SeekFrom};
+use super::Input;
+
+const BUF_SIZE: usize = 8 * 1024;
+
+/// Buffers the inner reader and keeps track of the number of bytes consumed,
+/// optionally recording them.
+///
+/// Buffering lets varints and short strings be parsed straight from memory,
+/// regardless of whether the inner reader is buffered itself.
+/// Inputs held in memory already are used directly instead.
+pub struct CountingReader<R> {
+    inner: R,
+    /// Whether the inner reader is held in memory, in which case it's used in place of the buffer
+    in_memory: bool,
+    /// Allocated on first use, so that decoding small in-memory values stays cheap
+    buf: Vec<u8>,
+    pos: usize,
+    filled: usize,
+    offset: u64,
+    recording: Option<Vec<u8>>,
+}
+
+impl<'de, R: Input<'de>> CountingReader<R> {
+    /// Counts from `offset`, for readers starting in the middle of a stream
+    pub fn with_offset(inner: R, offset: u64) -> Self {
+        CountingReader {
+            in_memory: inner.remaining().is_some(),
+            inner,
+            buf: Vec::new(),
+            pos: 0,
+            filled: 0,
+            offset,
+            recording: None,
+        }
+    }
+
+    /// Number of bytes consumed so far. Bytes that have been read
+    /// from the inner reader but are still buffered don't count.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Borrows the next `len` bytes from an input held in memory, and consumes them.
+    /// Returns `None` if the input isn't held in memory or too short.
+    pub fn borrow_bytes(&mut self, len: usize) -> Option<&'de [u8]> {
+        let bytes = self.inner.borrow_remaining()?.get(..len)?;
+        self.consume(len);
+        Some(bytes)
+    }
+
+    /// Starts keeping a copy of all bytes consumed from now on
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Vec::new());
+    }
+
+    /// Returns the bytes consumed since `start_recording`
+    pub fn stop_recording(&mut self) -> Vec<u8> {
+        self.recording.take().unwrap_or_default()
+    }
+}
+
+impl<'de, R: Input<'de> + Seek> CountingReader<R> {
+    /// Moves the inner reader to `offset`, discarding any buffered bytes
+    pub fn seek(&mut self, offset: u64) -> io::Result<()> {
+        self.inner.seek(SeekFrom::Start(offset))?;
+        self.pos = 0;
+        self.filled = 0;
+        self.offset = offset;
+        Ok(())
+    }
+}
+
+impl<'de, R: Input<'de>> Read for CountingReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        // Large reads bypass the buffer when it's empty anyway
+        if self.pos == self.filled && out.len() >= BUF_SIZE {
+            let n = self.inner.read(out)?;
+            self.offset += n as u64;
+
+            if let Some(ref mut recording) = self.recording {
+                recording.extend_from_slice(&out[..n]);
+            }
+
+            return Ok(n);
+        }
+
+        let n = {
+            let available = self.fill_buf()?;
+            let n = cmp::min(available.len(), out.len());
+            out[..n].copy_from_slice(&available[..n]);
+            n
+        };
+
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<'de, R: Input<'de>> BufRead for CountingReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.in_memory {
+            return Ok(self.inner.remaining().unwrap_or_default());
+        }
+
+        if self.pos == self.filled {
+            if self.buf.is_empty() {
+                self.buf = vec![0; BUF_SIZE];
+            }
+
+            self.filled = self.inner.read(&mut self.buf)?;
+            self.pos = 0;
+        }
+
+        Ok(&self.buf[self.pos..self.filled])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if self.in_memory {
+            let remaining = self.inner.remaining().unwrap_or_default();
+            let amt = cmp::min(amt, remaining.len());
+
+            if let Some(ref mut recording) = self.recording {
+                recording.extend_from_slice(&remaining[..amt]);
+            }
+
+            self.inner.advance(amt);
+            self.offset += amt as u64;
+            return;
+        }
+
+        let amt = cmp::min(amt, self.filled - self.pos);
+
+        if let Some(ref mut recording) = self.recording {
+            recording.extend_from_slice(&self.buf[self.pos..self.pos + amt]);
+        }
+
+        self.pos += amt;
+        self.offset += amt as u64;
+    }
+}
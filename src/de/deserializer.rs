@@ -1,106 +1,568 @@
-use std::io::Read;
-use std::rc::Rc;
+use std::io::{self,BufRead,Read};
+use std::mem;
+use std::str;
+use std::sync::Arc;
 use serde::{self,Deserialize};
-use serde::de::Visitor;
+use serde::de::{Visitor,DeserializeOwned};
 use errors::*;
-use types::{TypeId,TypeDef,WireType};
-use types::ids::*;
-use super::{ReadGob,ValueDeserializer};
+use types::{TypeId,TypeDef,WireType,Plan};
+use super::{ReadGob,ValueDeserializer,DeserializerOptions,CountingReader,StreamDeserializer,Input,SliceInput};
+use schema::TypeSchema;
 use TypeMap;
 
+/// Called with each type definition read from the stream, returning whether to accept it
+type DefineHook = Box<dyn FnMut(TypeId, &TypeSchema) -> bool + Send>;
+
 pub struct Deserializer<R> {
-    pub(crate) reader: R,
-    types: TypeMap,
+    pub(crate) reader: CountingReader<R>,
+    pub(super) types: TypeMap,
+    options: DeserializerOptions,
+    depth: usize,
+    pub(super) messages: u64,
+    /// The type of a value whose header has been read already
+    pub(super) pending: Option<TypeDef>,
+    /// Offset of the first message belonging to the current value
+    pub(super) item_offset: u64,
+    /// Offset at which the current message starts
+    pub(super) message_start: u64,
+    /// Offset at which the current message ends
+    message_end: u64,
+    on_define: Option<DefineHook>,
 }
 
-impl<R: Read> Deserializer<R> {
+impl<'de, R: Input<'de>> Deserializer<R> {
+    /// Creates a deserializer reading from `reader`.
+    ///
+    /// The reader is buffered internally, so there is no need to wrap it in a `BufReader`.
+    /// As a consequence, the deserializer may read past the last value it decodes.
     pub fn new(reader: R) -> Self {
+        Self::with_options(reader, DeserializerOptions::default())
+    }
+
+    pub fn with_options(reader: R, options: DeserializerOptions) -> Self {
+        Self::starting_at(reader, options, TypeMap::new(), 0, 0)
+    }
+
+    /// Creates a deserializer for a part of a stream starting at `offset`,
+    /// after `messages` messages which defined `types`.
+    /// Errors then locate themselves within the whole stream.
+    pub(crate) fn starting_at(reader: R, options: DeserializerOptions, types: TypeMap, offset: u64, messages: u64) -> Self {
         Deserializer {
-            reader,
-            types: TypeMap::new(),
+            reader: CountingReader::with_offset(reader, offset),
+            types,
+            options,
+            depth: 0,
+            messages,
+            pending: None,
+            item_offset: offset,
+            message_start: offset,
+            message_end: offset,
+            on_define: None,
         }
     }
 
-    pub fn deserialize<'de, T: Deserialize<'de>>(&mut self) -> Result<T> {
+    pub fn options(&self) -> &DeserializerOptions {
+        &self.options
+    }
+
+    pub fn set_options(&mut self, options: DeserializerOptions) {
+        self.options = options;
+    }
+
+    /// Returns a snapshot of the types defined so far
+    pub fn type_map(&self) -> TypeMap {
+        self.types.clone()
+    }
+
+    /// Describes the custom types defined so far, ordered by id
+    pub fn types(&self) -> Vec<TypeSchema> {
+        self.types.types()
+    }
+
+    /// Sets a hook that is called with each type the stream defines, e.g. to log schema changes.
+    ///
+    /// Types are defined in batches, which end before the next value.
+    /// The hook is called once the whole batch has been checked and registered.
+    /// If it returns false, the definition is vetoed: the batch is undefined again,
+    /// and decoding fails with `ErrorKind::TypeRejected`.
+    ///
+    /// ```no_run
+    /// # extern crate gob;
+    /// # use std::net::TcpStream;
+    /// # fn main() -> gob::Result<()> {
+    /// let mut de = gob::Deserializer::new(TcpStream::connect("producer:9000")?);
+    ///
+    /// de.on_define(|type_id, schema| {
+    ///     println!("type {} defined as {}", type_id, schema.name());
+    ///     schema.name().starts_with("trading.")
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_define<F>(&mut self, hook: F)
+        where F: FnMut(TypeId, &TypeSchema) -> bool + Send + 'static
+    {
+        self.on_define = Some(Box::new(hook));
+    }
+
+    /// Replaces the known types, e.g. with a snapshot taken from another deserializer.
+    /// Values referring to these types can then be decoded without their definitions.
+    pub fn set_type_map(&mut self, types: TypeMap) {
+        self.types = types;
+    }
+
+    /// Deserializes the next value, borrowing from the input if `R` allows it.
+    /// Public callers go through `deserialize`, which only allows borrowing from a `SliceInput`.
+    pub(crate) fn deserialize_next<T: Deserialize<'de>>(&mut self) -> Result<T> {
         T::deserialize(self)
     }
 
+
+    /// Reads any pending type definitions and describes the type of the next value,
+    /// without consuming the value itself. Returns `None` at the end of the stream.
+    pub fn peek_type(&mut self) -> Result<Option<TypeSchema>> {
+        self.read_pending()?;
+        Ok(self.pending.as_ref().map(TypeSchema::new))
+    }
+
+    /// Reads the header of the next value unless that happened already.
+    /// Returns false if the stream ended before the next message.
+    pub(crate) fn read_pending(&mut self) -> Result<bool> {
+        if self.pending.is_some() {
+            return Ok(true);
+        }
+
+        match self.read_value_header() {
+            Ok(Some(type_def)) => {
+                self.pending = Some(type_def);
+                Ok(true)
+            }
+            Ok(None) => Ok(false),
+            Err(err) => Err(err.at(self.offset(), self.messages, None)),
+        }
+    }
+
+    /// Number of bytes read from the underlying reader so far
+    pub fn offset(&self) -> u64 {
+        self.reader.offset()
+    }
+
+    fn deserialize_next_with_options<T: Deserialize<'de>>(&mut self, options: DeserializerOptions) -> Result<T> {
+        let options = mem::replace(&mut self.options, options);
+        let result = T::deserialize(&mut *self);
+        self.options = options;
+        result
+    }
+
+    /// Offset of the message boundary before the next value,
+    /// which precedes its header if that has been read already
+    pub(super) fn boundary(&self) -> u64 {
+        match self.pending {
+            Some(_) => self.message_start,
+            None => self.offset(),
+        }
+    }
+
     pub(super) fn look_up_type(&self, type_id: TypeId) -> Result<TypeDef> {
-        TypeDef::from_id(type_id, &self.types).ok_or(ErrorKind::UndefinedType(type_id).into())
+        TypeDef::from_id(type_id, &self.types).ok_or(Corruption::UndefinedType(type_id).into())
+    }
+
+    /// The decode plan of a custom type
+    pub(super) fn plan(&self, type_id: TypeId) -> Result<Arc<Plan>> {
+        self.types.plan(type_id).cloned().ok_or_else(|| Corruption::UndefinedType(type_id).into())
+    }
+
+    /// Reads and registers type definitions up to the next value,
+    /// and returns the type of that value.
+    /// Returns `None` if the stream ends at a message boundary instead.
+    fn read_value_header(&mut self) -> Result<Option<TypeDef>> {
+        self.item_offset = self.offset();
+
+        let mut defined = Vec::new();
+        let type_id = match self.read_definitions(&mut defined) {
+            Ok(type_id) => type_id,
+            Err(err) => {
+                // Definitions read before the error would be left uncommitted
+                self.types.undefine(&defined);
+                return Err(err);
+            }
+        };
+
+        self.commit(&defined)?;
+
+        let type_id = match type_id {
+            Some(type_id) => type_id,
+            None => return Ok(None),
+        };
+
+        trace!("Decoding type {}", type_id);
+
+        let type_def = TypeDef::from_id(type_id, &self.types)
+            .ok_or(Corruption::UndefinedType(type_id))?;
+
+        // Values other than structs are sent like the only field of a struct
+        if !type_def.is_struct() && self.reader.read_gob_usize()? != 0 {
+            bail!(Corruption::NonZeroSingletonDelta)
+        }
+
+        Ok(Some(type_def))
+    }
+
+    /// Reads as many type definitions as possible, collecting them in `defined`,
+    /// and returns the type id of the following value.
+    /// Returns `None` if the stream ends at a message boundary instead.
+    fn read_definitions(&mut self, defined: &mut Vec<TypeId>) -> Result<Option<TypeId>> {
+        loop {
+            let type_id = match self.read_message_header()? {
+                Some(type_id) => type_id,
+                None => return Ok(None),
+            };
+
+            if type_id >= 0 {
+                return Ok(Some(type_id)) // The following data is a value, not a definition
+            }
+
+            defined.push(self.read_definition(-type_id)?);
+            self.messages += 1;
+        }
+    }
+
+    /// Reads the length and type id of the next message.
+    /// The id is negative if the message defines that type.
+    /// Returns `None` if the stream ends at a message boundary.
+    pub(super) fn read_message_header(&mut self) -> Result<Option<TypeId>> {
+        if self.reader.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+
+        self.message_start = self.offset();
+        let len = self.reader.read_gob_usize()?;
+
+        trace!("Len: {}", len);
+
+        if len > self.options.max_message_size {
+            bail!(Limit::MessageSize(len))
+        }
+
+        self.message_end = self.offset().checked_add(len as u64).ok_or(Limit::MessageSize(len))?;
+
+        Ok(Some(self.reader.read_gob_type_id()?))
+    }
+
+    /// Reads the next message, registering the type it defines or skipping the value it holds.
+    /// Definitions are collected in `defined` and committed before the next value.
+    /// Returns the message's type id, which is negative for definitions,
+    /// or `None` if the stream ends at a message boundary.
+    ///
+    /// On error, the definitions collected so far are undefined again.
+    pub(super) fn scan_message(&mut self, defined: &mut Vec<TypeId>) -> Result<Option<TypeId>> {
+        let result = self.scan_next_message(defined);
+        if result.is_err() {
+            self.types.undefine(defined);
+            defined.clear();
+        }
+        result
+    }
+
+    fn scan_next_message(&mut self, defined: &mut Vec<TypeId>) -> Result<Option<TypeId>> {
+        let type_id = match self.read_message_header()? {
+            Some(type_id) => type_id,
+            None => {
+                self.commit(defined)?;
+                return Ok(None);
+            }
+        };
+
+        if type_id < 0 {
+            defined.push(self.read_definition(-type_id)?);
+        } else {
+            self.commit(defined)?;
+            defined.clear();
+            self.look_up_type(type_id)?;
+            self.skip_message()?;
+        }
+
+        self.messages += 1;
+        Ok(Some(type_id))
+    }
+
+    /// Reads the definition of a type and adds it to the known types.
+    /// It can't be used until its batch of definitions is committed.
+    pub(super) fn read_definition(&mut self, type_id: TypeId) -> Result<TypeId> {
+        trace!("Defining type {}", type_id);
+
+        let wire_type = WireType::deserialize(&mut ValueDeserializer::new(self, TypeDef::WireType))?
+            .try_into_enum()?;
+        self.check_message_end()?;
+
+        trace!("Type def: {:#?}", wire_type);
+
+        if type_id != wire_type.id() {
+            bail!(Corruption::DefiningIdMismatch(type_id, wire_type.id()))
+        }
+
+        if self.types.len() >= self.options.max_types {
+            bail!(Limit::Types(self.options.max_types))
+        }
+
+        self.types.define(wire_type)
+    }
+
+    /// Commits a batch of definitions and passes them to the definition hook
+    fn commit(&mut self, defined: &[TypeId]) -> Result<()> {
+        self.types.commit(defined)?;
+
+        let hook = match self.on_define {
+            Some(ref mut hook) => hook,
+            None => return Ok(()),
+        };
+
+        for &type_id in defined {
+            let schema = match self.types.get(type_id) {
+                Some(type_def) => TypeSchema::new(type_def),
+                None => continue,
+            };
+
+            if !hook(type_id, &schema) {
+                self.types.undefine(defined);
+                bail!(ErrorKind::TypeRejected {
+                    type_id,
+                    type_name: schema.name().into(),
+                })
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The type of the value whose header has been read already
+    pub(crate) fn pending_type(&self) -> Option<&TypeDef> {
+        self.pending.as_ref()
+    }
+
+    /// Consumes the pending value without decoding it
+    pub(crate) fn skip_pending(&mut self) -> Result<()> {
+        self.pending = None;
+        self.skip_message()?;
+        self.messages += 1;
+
+        Ok(())
     }
 
-    pub(super) fn reader(&mut self) -> &mut R {
+    /// Skips the rest of the current message
+    pub(super) fn skip_message(&mut self) -> Result<()> {
+        let len = self.remaining() as usize;
+        self.reader.skip_gob_exact(len)
+    }
+
+    /// Decodes the next value with `f`, given the value's type,
+    /// and attaches the position in the stream to any error.
+    pub(super) fn deserialize_message<T, F>(&mut self, f: F) -> Result<T>
+        where F: FnOnce(&mut Self, TypeDef) -> Result<T>
+    {
+        let header = match self.pending.take() {
+            Some(type_def) => Ok(type_def),
+            None => self.read_value_header().and_then(|header| {
+                header.ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof).into())
+            }),
+        };
+
+        match header {
+            Ok(type_def) => {
+                let result = f(self, type_def.clone())
+                    .and_then(|value| self.check_message_end().map(|()| value))
+                    .map_err(|err| err.at(self.reader.offset(), self.messages, Some(type_def.name())));
+
+                // The value's message counts as read even if decoding it failed
+                self.messages += 1;
+                result
+            }
+            Err(err) => Err(err.at(self.reader.offset(), self.messages, None)),
+        }
+    }
+
+    pub(super) fn reader(&mut self) -> &mut CountingReader<R> {
         &mut self.reader
     }
 
-    pub(super) fn deserialize_seq<'de, V>(&mut self, visitor: V, type_def: TypeDef) -> Result<V::Value>
-        where V: Visitor<'de>
+    /// Passes the next `len` bytes to `f`,
+    /// straight from the read buffer if they are buffered completely.
+    fn with_bytes<T, F>(&mut self, len: usize, f: F) -> Result<T>
+        where F: FnOnce(&[u8]) -> Result<T>
     {
-        visitor.visit_seq(super::SeqAccess::new(self, type_def)?)
+        let result = match self.reader.fill_buf()?.get(..len) {
+            Some(bytes) => f(bytes),
+            None => return f(&self.reader.read_gob_exact(len)?),
+        };
+
+        self.reader.consume(len);
+        result
     }
 
-    pub(super) fn deserialize_map<'de, V>(&mut self, visitor: V, type_def: TypeDef) -> Result<V::Value>
+    /// Reads length prefixed bytes and passes them to `visitor`,
+    /// borrowed from the input if it's held in memory.
+    /// Valid UTF-8 is passed as a string if `is_string` is set.
+    pub(super) fn visit_bytes<V>(&mut self, visitor: V, is_string: bool) -> Result<V::Value>
         where V: Visitor<'de>
     {
-        visitor.visit_map(super::MapAccess::new(self, type_def))
+        let len = self.read_bytes_len()?;
+
+        match self.reader.borrow_bytes(len) {
+            Some(bytes) => match str::from_utf8(bytes) {
+                Ok(s) if is_string => visitor.visit_borrowed_str(s),
+                _ => visitor.visit_borrowed_bytes(bytes),
+            },
+            None => self.with_bytes(len, |bytes| visitor.visit_bytes(bytes)),
+        }
+    }
+
+    fn read_bytes_len(&mut self) -> Result<usize> {
+        let len = self.reader.read_gob_usize()?;
+        self.check_bytes_len(len)?;
+        Ok(len)
+    }
+
+    /// Checks the length of bytes about to be read against the message size limit
+    /// and the rest of the current message
+    pub(super) fn check_bytes_len(&self, len: usize) -> Result<()> {
+        if len > self.options.max_message_size {
+            bail!(Limit::MessageSize(len));
+        }
+
+        if len as u64 > self.remaining() {
+            bail!(Corruption::LengthPastMessageEnd(len));
+        }
+
+        Ok(())
+    }
+
+    /// Number of bytes left in the current message
+    pub(super) fn remaining(&self) -> u64 {
+        self.message_end.saturating_sub(self.offset())
+    }
+
+    /// Checks that the current message has been read up to its end, but not past it
+    fn check_message_end(&self) -> Result<()> {
+        if self.offset() != self.message_end {
+            bail!(Corruption::MessageLength {
+                expected_end: self.message_end,
+                end: self.offset(),
+            })
+        }
+
+        Ok(())
     }
 
-    pub(super) fn deserialize_value<'de, V>(&mut self, visitor: V, type_def: TypeDef) -> Result<V::Value>
+    /// Runs `f` one nesting level deeper, enforcing the depth limit
+    pub(super) fn nested<T, F>(&mut self, f: F) -> Result<T>
+        where F: FnOnce(&mut Self) -> Result<T>
+    {
+        if self.depth >= self.options.max_depth {
+            bail!(Limit::Depth(self.options.max_depth));
+        }
+
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    pub(super) fn deserialize_seq<V>(&mut self, visitor: V, type_def: TypeDef) -> Result<V::Value>
         where V: Visitor<'de>
     {
-        let ref mut de = ValueDeserializer::new(self, type_def);
-        serde::Deserializer::deserialize_any(de, visitor)
+        self.nested(|de| visitor.visit_seq(super::SeqAccess::new(de, type_def)?))
     }
-}
 
-impl<'a, 'de, R: Read> serde::Deserializer<'de> for &'a mut Deserializer<R> {
-    type Error = Error;
+    pub(super) fn deserialize_map<V>(&mut self, visitor: V, type_def: TypeDef) -> Result<V::Value>
+        where V: Visitor<'de>
+    {
+        self.nested(|de| visitor.visit_map(super::MapAccess::new(de, type_def, None)))
+    }
 
-    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    pub(super) fn deserialize_struct<V>(&mut self, visitor: V, type_def: TypeDef, plan: Arc<Plan>) -> Result<V::Value>
         where V: Visitor<'de>
     {
-        let mut len;
-        let mut type_id;
+        self.nested(|de| visitor.visit_map(super::MapAccess::new(de, type_def, Some(plan))))
+    }
 
-        // Read as many type definitions as possible
-        loop {
-            len = self.reader.read_gob_usize()?;
-            type_id = self.reader.read_gob_type_id()?;
+    pub(super) fn deserialize_entries<V>(&mut self, visitor: V, key_def: TypeDef, elem_def: TypeDef) -> Result<V::Value>
+        where V: Visitor<'de>
+    {
+        self.nested(|de| visitor.visit_map(super::EntryAccess::new(de, key_def, elem_def)?))
+    }
 
-            trace!("Len: {}", len);
+    pub(super) fn deserialize_value<V>(&mut self, visitor: V, type_def: TypeDef) -> Result<V::Value>
+        where V: Visitor<'de>
+    {
+        let ref mut de = ValueDeserializer::new(self, type_def);
+        serde::Deserializer::deserialize_any(de, visitor)
+    }
+}
 
-            if type_id >= 0 { break } // The following data is a value, not a definition
+/// Values decoded from an `io::Read` can't borrow from it, so they must be owned
+impl<R: Read> Deserializer<R> {
+    pub fn deserialize<T: DeserializeOwned>(&mut self) -> Result<T> {
+        self.deserialize_next()
+    }
 
-            type_id = -type_id;
+    /// Deserializes the next value using the given options instead of the configured ones
+    pub fn deserialize_with_options<T: DeserializeOwned>(&mut self, options: DeserializerOptions) -> Result<T> {
+        self.deserialize_next_with_options(options)
+    }
 
-            trace!("Defining type {}", type_id);
+    /// Returns an iterator over the remaining values of the stream
+    #[allow(clippy::should_implement_trait)]
+    pub fn into_iter<T: DeserializeOwned>(self) -> StreamDeserializer<R, T> {
+        StreamDeserializer::new(self)
+    }
 
-            let type_def = WireType::deserialize(&mut ValueDeserializer::new(self, TypeDef::WireType))?
-                .try_into_enum()
-                .map(Rc::new)
-                .map(TypeDef::Custom)?;
+    /// Returns an iterator over the remaining values of the stream,
+    /// leaving the deserializer usable afterwards
+    pub fn iter<T: DeserializeOwned>(&mut self) -> StreamDeserializer<R, T, &mut Self> {
+        StreamDeserializer::new(self)
+    }
+}
 
-            trace!("Type def: {:#?}", type_def);
+/// Values decoded from memory may borrow strings and byte slices from it
+impl<'de> Deserializer<SliceInput<'de>> {
+    pub fn deserialize<T: Deserialize<'de>>(&mut self) -> Result<T> {
+        self.deserialize_next()
+    }
 
-            if type_id != type_def.id() {
-                bail!(ErrorKind::DefiningIdMismatch(type_id, type_def.id()))
-            }
+    /// Deserializes the next value using the given options instead of the configured ones
+    pub fn deserialize_with_options<T: Deserialize<'de>>(&mut self, options: DeserializerOptions) -> Result<T> {
+        self.deserialize_next_with_options(options)
+    }
 
-            if is_type_builtin(type_id) {
-                bail!(ErrorKind::DefiningBuiltin(type_id))
-            }
+    /// Returns an iterator over the remaining values of the stream,
+    /// which may borrow from it
+    #[allow(clippy::should_implement_trait)]
+    pub fn into_iter<T: Deserialize<'de>>(self) -> StreamDeserializer<SliceInput<'de>, T> {
+        StreamDeserializer::new(self)
+    }
 
-            if self.types.insert(type_def.id(), type_def).is_some() {
-                bail!(ErrorKind::TypeAlreadyDefined(type_id))
-            };
-        }
+    /// Returns an iterator over the remaining values of the stream,
+    /// leaving the deserializer usable afterwards
+    pub fn iter<T: Deserialize<'de>>(&mut self) -> StreamDeserializer<SliceInput<'de>, T, &mut Self> {
+        StreamDeserializer::new(self)
+    }
+}
 
-        trace!("Decoding type {}", type_id);
+impl<'a, 'de, R: Input<'de>> serde::Deserializer<'de> for &'a mut Deserializer<R> {
+    type Error = Error;
 
-        let type_def = TypeDef::from_id(type_id, &self.types)
-            .ok_or(ErrorKind::UndefinedType(type_id))?;
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+        where V: Visitor<'de>
+    {
+        self.deserialize_message(|de, type_def| de.deserialize_value(visitor, type_def))
+    }
 
-        self.deserialize_value(visitor, type_def)
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+        where V: Visitor<'de>
+    {
+        self.deserialize_message(|de, type_def| {
+            serde::Deserializer::deserialize_newtype_struct(&mut ValueDeserializer::new(de, type_def), name, visitor)
+        })
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
@@ -112,27 +574,7 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &'a mut Deserializer<R> {
 
     forward_to_deserialize_any! {
         bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
-        byte_buf unit unit_struct newtype_struct seq tuple
+        byte_buf unit unit_struct seq tuple
         tuple_struct map struct enum identifier ignored_any
     }
 }
-
-fn is_type_builtin(id: TypeId) -> bool {
-    [
-        BOOL_ID,
-        INT_ID,
-        UINT_ID,
-        FLOAT_ID,
-        BYTE_SLICE_ID,
-        STRING_ID,
-        COMPLEX_ID,
-        INTERFACE_ID,
-        WIRE_TYPE_ID,
-        ARRAY_TYPE_ID,
-        COMMON_TYPE_ID,
-        SLICE_TYPE_ID,
-        STRUCT_TYPE_ID,
-        FIELD_TYPE_ID,
-        MAP_TYPE_ID,
-    ].contains(&id)
-}
@@ -0,0 +1,183 @@
+use errors::*;
+use types::{TypeDef,WireTypeEnum,Plan,FieldPlan};
+use value::{Value,Interface};
+use super::{Deserializer,ReadGob,Input};
+use super::map_access::{field_name,field_type};
+
+impl<'de, R: Input<'de>> Deserializer<R> {
+    /// Decodes a value of the given type into a `Value`
+    pub(super) fn read_dynamic(&mut self, type_def: &TypeDef) -> Result<Value> {
+        let value = match *type_def {
+            TypeDef::Bool => Value::Bool(self.reader.read_gob_bool()?),
+            TypeDef::Int => Value::Int(self.reader.read_gob_i64()?),
+            TypeDef::Uint => Value::Uint(self.reader.read_gob_u64()?),
+            TypeDef::Float => Value::Float(self.reader.read_gob_f64()?),
+            TypeDef::Complex => {
+                let re = self.reader.read_gob_f64()?;
+                let im = self.reader.read_gob_f64()?;
+                Value::Complex(re, im)
+            }
+            TypeDef::ByteSlice => Value::Bytes(self.read_dynamic_bytes()?),
+            TypeDef::String => Value::String(self.read_dynamic_string()?),
+            TypeDef::Interface => Value::Interface(self.read_dynamic_interface()?),
+            TypeDef::FieldTypeSlice => Value::Slice {
+                type_name: type_def.name().into(),
+                elems: self.read_dynamic_elements(&TypeDef::FieldType)?,
+            },
+            TypeDef::Custom(ref wire_type) => {
+                let type_name = wire_type.name().into();
+
+                match *self.plan(wire_type.id())? {
+                    Plan::Struct(ref fields) => Value::Struct {
+                        type_name,
+                        fields: self.read_dynamic_fields(type_def, Some(fields))?,
+                    },
+                    Plan::Elements(ref elem_def) => {
+                        let elems = self.read_dynamic_elements(elem_def)?;
+                        match **wire_type {
+                            WireTypeEnum::Array(ref t) => {
+                                if elems.len() as isize != t.len() {
+                                    bail!(Corruption::ArrayLength {
+                                        type_name,
+                                        expected: t.len() as usize,
+                                        len: elems.len(),
+                                    })
+                                }
+                                Value::Array { type_name, elems }
+                            }
+                            _ => Value::Slice { type_name, elems },
+                        }
+                    }
+                    Plan::Entries(ref key_def, ref elem_def) => Value::Map {
+                        type_name,
+                        entries: self.read_dynamic_entries(key_def, elem_def)?,
+                    },
+                }
+            }
+            TypeDef::WireType
+            | TypeDef::ArrayType
+            | TypeDef::CommonType
+            | TypeDef::SliceType
+            | TypeDef::StructType
+            | TypeDef::FieldType
+            | TypeDef::MapType => Value::Struct {
+                type_name: type_def.name().into(),
+                fields: self.read_dynamic_fields(type_def, None)?,
+            },
+        };
+
+        Ok(value)
+    }
+
+    /// Reads the fields of a struct, named by the plan of custom types
+    /// or by the layout of the builtin ones
+    fn read_dynamic_fields(&mut self, type_def: &TypeDef, plan: Option<&[FieldPlan]>) -> Result<Vec<(String, Value)>> {
+        self.nested(|de| {
+            let mut fields = Vec::new();
+            let mut current_field = -1isize;
+
+            loop {
+                let field_increment = de.reader.read_gob_usize()?;
+                if field_increment == 0 {
+                    return Ok(fields);
+                }
+
+                current_field += field_increment as isize;
+                let field_id = current_field as usize;
+
+                let (name, field_def) = match plan {
+                    Some(plan) => {
+                        let field = plan.get(field_id).ok_or(Corruption::InvalidField(field_id))?;
+                        (field.name.clone(), field.type_def.clone())
+                    }
+                    None => (field_name(type_def, field_id)?.into(), field_type(type_def, field_id)?),
+                };
+
+                let value = de.read_dynamic(&field_def).map_err(|err| err.in_field(&name))?;
+                fields.push((name, value));
+            }
+        })
+    }
+
+    fn read_dynamic_elements(&mut self, elem_def: &TypeDef) -> Result<Vec<Value>> {
+        self.nested(|de| {
+            let len = de.read_len()?;
+            let mut elems = Vec::with_capacity(len);
+
+            for index in 0..len {
+                elems.push(de.read_dynamic(elem_def).map_err(|err| err.in_element(index))?);
+            }
+
+            Ok(elems)
+        })
+    }
+
+    fn read_dynamic_entries(&mut self, key_def: &TypeDef, elem_def: &TypeDef) -> Result<Vec<(Value, Value)>> {
+        self.nested(|de| {
+            let len = de.read_len()?;
+            let mut entries = Vec::with_capacity(len);
+
+            for index in 0..len {
+                let key = de.read_dynamic(key_def).map_err(|err| err.in_element(index))?;
+                let elem = de.read_dynamic(elem_def).map_err(|err| err.in_element(index))?;
+                entries.push((key, elem));
+            }
+
+            Ok(entries)
+        })
+    }
+
+    /// An interface value is the name of its concrete type,
+    /// followed by the type's id and the length prefixed value, unless it's nil.
+    /// The value is encoded like a value at the top level of the stream.
+    fn read_dynamic_interface(&mut self) -> Result<Option<Box<Interface>>> {
+        let name_len = self.reader.read_gob_usize()?;
+        if name_len == 0 {
+            return Ok(None);
+        }
+
+        let name = into_string(self.read_dynamic_bytes_of_len(name_len)?);
+
+        let type_id = self.reader.read_gob_type_id()?;
+        if type_id < 0 {
+            bail!(ErrorKind::Unsupported("type definitions inside interface values".into()))
+        }
+
+        let type_def = self.look_up_type(type_id)?;
+        let len = self.reader.read_gob_usize()?;
+        self.check_bytes_len(len)?;
+        let end = self.offset() + len as u64;
+
+        // Values other than structs are sent like the only field of a struct
+        if !type_def.is_struct() && self.reader.read_gob_usize()? != 0 {
+            bail!(Corruption::NonZeroSingletonDelta)
+        }
+
+        let value = self.nested(|de| de.read_dynamic(&type_def))?;
+
+        if self.offset() != end {
+            bail!(Corruption::InterfaceLength(len))
+        }
+
+        Ok(Some(Box::new(Interface { name, value })))
+    }
+
+    fn read_dynamic_string(&mut self) -> Result<String> {
+        self.read_dynamic_bytes().map(into_string)
+    }
+
+    fn read_dynamic_bytes(&mut self) -> Result<Vec<u8>> {
+        let len = self.reader.read_gob_usize()?;
+        self.read_dynamic_bytes_of_len(len)
+    }
+
+    fn read_dynamic_bytes_of_len(&mut self, len: usize) -> Result<Vec<u8>> {
+        self.check_bytes_len(len)?;
+        self.reader.read_gob_exact(len)
+    }
+}
+
+/// Go strings may hold any bytes, so invalid UTF-8 is replaced rather than rejected
+fn into_string(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes).unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).into_owned())
+}
@@ -0,0 +1,57 @@
+use serde;
+use errors::*;
+use types::TypeDef;
+use super::{Deserializer,ValueDeserializer,Input};
+
+/// Provides access to the entries of a gob map
+pub struct EntryAccess<'a, R: 'a> {
+    de: &'a mut Deserializer<R>,
+    len: usize,
+    current_index: usize,
+    key_def: TypeDef,
+    elem_def: TypeDef,
+}
+
+impl<'a, 'de, R: Input<'de> + 'a> EntryAccess<'a, R> {
+    pub fn new(de: &'a mut Deserializer<R>, key_def: TypeDef, elem_def: TypeDef) -> Result<Self> {
+        let len = de.read_len()?;
+
+        Ok(EntryAccess {
+            de,
+            len,
+            current_index: 0,
+            key_def,
+            elem_def,
+        })
+    }
+}
+
+impl<'a, 'de, R: Input<'de>> serde::de::MapAccess<'de> for EntryAccess<'a, R> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+        where K: serde::de::DeserializeSeed<'de>
+    {
+        if self.current_index >= self.len {
+            return Ok(None);
+        }
+
+        self.current_index += 1;
+
+        let index = self.current_index - 1;
+        let de = &mut ValueDeserializer::new(self.de, self.key_def.clone());
+        seed.deserialize(de).map(Some).map_err(|err| err.in_element(index))
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+        where V: serde::de::DeserializeSeed<'de>
+    {
+        let index = self.current_index - 1;
+        let de = &mut ValueDeserializer::new(self.de, self.elem_def.clone());
+        seed.deserialize(de).map_err(|err| err.in_element(index))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.current_index)
+    }
+}
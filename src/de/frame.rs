@@ -0,0 +1,189 @@
+use std::mem::size_of;
+use serde::de::DeserializeOwned;
+use errors::*;
+use types::{TypeId,TypeMap};
+use super::{Deserializer,DeserializerOptions,SliceInput};
+use super::read_gob::parse_buffered_uint;
+
+/// Decodes a stream that arrives in pieces, one frame at a time.
+///
+/// A frame is a value along with the type definitions in front of it.
+/// The types defined by earlier frames are kept, so each frame is decoded
+/// as part of the whole stream.
+#[derive(Debug,Clone)]
+pub(crate) struct FrameDecoder {
+    pub types: TypeMap,
+    pub options: DeserializerOptions,
+    /// Offset of the next frame in the stream
+    pub offset: u64,
+    pub messages: u64,
+}
+
+impl FrameDecoder {
+    pub fn new(options: DeserializerOptions) -> Self {
+        FrameDecoder {
+            types: TypeMap::new(),
+            options,
+            offset: 0,
+            messages: 0,
+        }
+    }
+
+    /// Finds the end of the frame at the start of `buf`.
+    /// Returns `None` if `buf` doesn't hold all messages of the frame yet.
+    #[cfg(feature = "tokio")]
+    pub fn frame_len(&self, buf: &[u8]) -> Result<Option<usize>> {
+        self.scan_frame(buf, &mut Vec::new())
+    }
+
+    /// Like `frame_len`, also collecting the ids of the types defined by the complete messages in `buf`
+    pub fn scan_frame(&self, buf: &[u8], defined: &mut Vec<TypeId>) -> Result<Option<usize>> {
+        value_len(buf, &self.options, self.types.len(), defined)
+            .map_err(|err| err.at(self.offset, self.messages, None))
+    }
+
+    /// Decodes a frame found by `frame_len`.
+    ///
+    /// At the end of the stream, this also takes whatever is left:
+    /// trailing definitions are registered and `None` is returned,
+    /// while a partial message is an error.
+    #[cfg(feature = "tokio")]
+    pub fn decode<T: DeserializeOwned>(&mut self, frame: &[u8]) -> Result<Option<T>> {
+        self.decode_frame(frame).1
+    }
+
+    /// Like `decode`, also telling whether the type definitions of the frame were applied.
+    /// They are applied even if the value fails to decode.
+    pub fn decode_frame<T: DeserializeOwned>(&mut self, frame: &[u8]) -> (bool, Result<Option<T>>) {
+        let input = SliceInput::new(frame);
+        let mut de = Deserializer::starting_at(input, self.options.clone(), self.types.clone(), self.offset, self.messages);
+
+        let found = de.read_pending();
+        let applied = found.is_ok();
+
+        if applied {
+            self.types = de.type_map();
+        }
+
+        let value = match found {
+            Ok(true) => de.deserialize().map(Some),
+            Ok(false) => Ok(None),
+            Err(err) => Err(err),
+        };
+
+        self.messages = de.messages;
+        self.offset += frame.len() as u64;
+
+        (applied, value)
+    }
+}
+
+/// Bytes of a stream waiting to be decoded, which start at a message boundary.
+///
+/// Decoded bytes are only dropped from the front once they make up more than half
+/// of the buffer, so that decoding many small frames doesn't move the rest over and over.
+#[derive(Debug,Default)]
+pub(crate) struct FrameBuffer {
+    bytes: Vec<u8>,
+    /// Offset of the first byte that hasn't been decoded yet
+    start: usize,
+}
+
+impl FrameBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The bytes that haven't been decoded yet
+    pub fn pending(&self) -> &[u8] {
+        &self.bytes[self.start..]
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len() - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn extend(&mut self, bytes: &[u8]) {
+        if self.start > self.bytes.len() / 2 {
+            self.bytes.drain(..self.start);
+            self.start = 0;
+        }
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    /// Drops the first `len` pending bytes, which have been decoded
+    pub fn consume(&mut self, len: usize) {
+        self.start += len;
+        if self.start == self.bytes.len() {
+            self.bytes.clear();
+            self.start = 0;
+        }
+    }
+
+    /// Returns the bytes that haven't been decoded yet
+    #[cfg(feature = "tokio")]
+    pub fn into_vec(mut self) -> Vec<u8> {
+        self.bytes.drain(..self.start);
+        self.bytes
+    }
+}
+
+/// Finds the end of the first value in `buf`, including the type definitions in front of it,
+/// and collects the ids of the types defined on the way.
+///
+/// Only the message headers are looked at, so a value found here can still fail to decode.
+/// The type limit is enforced on the way, counting the `known` types,
+/// so that a peer can't make the buffer grow by sending definitions only.
+fn value_len(buf: &[u8], options: &DeserializerOptions, known: usize, defined: &mut Vec<TypeId>) -> Result<Option<usize>> {
+    let mut pos = 0;
+
+    loop {
+        let (len, prefix_len) = match parse_buffered_uint(&buf[pos..], size_of::<u64>())? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+
+        if len > options.max_message_size as u64 {
+            bail!(Limit::MessageSize(len as usize))
+        }
+
+        let start = pos + prefix_len;
+        let end = start.checked_add(len as usize).ok_or(Limit::MessageSize(len as usize))?;
+
+        let message = match buf.get(start..end) {
+            Some(message) => message,
+            None => return Ok(None),
+        };
+
+        pos = end;
+
+        let type_id = match parse_buffered_uint(message, size_of::<u64>()) {
+            Ok(Some((bits, _))) => decode_type_id(bits),
+            // Left for the deserializer to reject
+            _ => return Ok(Some(pos)),
+        };
+
+        if type_id >= 0 {
+            return Ok(Some(pos));
+        }
+
+        // The message defines a type, so the value comes later
+        defined.push(-type_id);
+
+        if known + defined.len() > options.max_types {
+            bail!(Limit::Types(options.max_types))
+        }
+    }
+}
+
+fn decode_type_id(bits: u64) -> TypeId {
+    if bits & 1 == 1 {
+        !((bits >> 1) as TypeId)
+    } else {
+        (bits >> 1) as TypeId
+    }
+}
@@ -0,0 +1,176 @@
+use std::io::{self,Seek};
+use errors::*;
+use types::{TypeId,TypeMap};
+use super::{Deserializer,DeserializerOptions,Input};
+
+/// The positions of all messages of a gob stream, built in a single pass.
+///
+/// Together with a seekable reader, an index gives random access to the values of the stream:
+/// [`Deserializer::seek_value`] jumps straight to the n-th value.
+/// The index also keeps all types defined by the stream, so values can be decoded
+/// without reading any definitions again.
+///
+/// Indexes can be saved with serde, e.g. to a sidecar file next to the stream.
+///
+/// ```no_run
+/// # extern crate gob;
+/// # extern crate serde_json;
+/// # use std::fs::File;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let index = gob::Index::build(File::open("trades.gob")?)?;
+/// serde_json::to_writer(File::create("trades.gob.idx")?, &index)?;
+///
+/// let mut de = gob::Deserializer::new(File::open("trades.gob")?);
+/// de.seek_value(&index, index.len() - 1)?;
+/// let last: u64 = de.deserialize()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Deserializer::seek_value`]: struct.Deserializer.html#method.seek_value
+#[derive(Debug,Clone,Default,Serialize,Deserialize)]
+#[serde(from="IndexData")]
+pub struct Index {
+    types: TypeMap,
+    messages: Vec<IndexEntry>,
+    /// Positions of the value messages in `messages`
+    #[serde(skip_serializing)]
+    values: Vec<usize>,
+}
+
+#[derive(Deserialize)]
+struct IndexData {
+    types: TypeMap,
+    messages: Vec<IndexEntry>,
+}
+
+/// The position and kind of a message
+#[derive(Debug,Clone,Copy,PartialEq,Serialize,Deserialize)]
+pub struct IndexEntry {
+    offset: u64,
+    len: u64,
+    type_id: TypeId,
+    definition: bool,
+}
+
+impl Index {
+    /// Reads a gob stream to its end and indexes its messages
+    pub fn build<'de, R: Input<'de>>(reader: R) -> Result<Self> {
+        Self::build_with_options(reader, DeserializerOptions::default())
+    }
+
+    pub fn build_with_options<'de, R: Input<'de>>(reader: R, options: DeserializerOptions) -> Result<Self> {
+        let mut de = Deserializer::with_options(reader, options);
+        let mut messages = Vec::new();
+
+        de.index_messages(&mut messages)
+            .map_err(|err| err.at(de.offset(), de.messages, None))?;
+
+        Ok(Index::from(IndexData {
+            types: de.type_map(),
+            messages,
+        }))
+    }
+
+    /// All messages of the stream, in order
+    pub fn messages(&self) -> &[IndexEntry] {
+        &self.messages
+    }
+
+    /// The value messages of the stream, in order
+    pub fn values(&self) -> impl Iterator<Item = &IndexEntry> {
+        self.values.iter().map(move |&message| &self.messages[message])
+    }
+
+    /// The n-th value message
+    pub fn value(&self, n: usize) -> Option<&IndexEntry> {
+        self.values.get(n).map(|&message| &self.messages[message])
+    }
+
+    /// Number of values
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// All types defined by the stream
+    pub fn type_map(&self) -> &TypeMap {
+        &self.types
+    }
+}
+
+impl From<IndexData> for Index {
+    fn from(data: IndexData) -> Self {
+        let values = data.messages.iter().enumerate()
+            .filter(|&(_, entry)| !entry.definition)
+            .map(|(message, _)| message)
+            .collect();
+
+        Index {
+            types: data.types,
+            messages: data.messages,
+            values,
+        }
+    }
+}
+
+impl IndexEntry {
+    /// Offset at which the message starts
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Size of the message in bytes, including its length prefix
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// The type defined by the message, or the type of its value
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    pub fn is_definition(&self) -> bool {
+        self.definition
+    }
+}
+
+impl<'de, R: Input<'de>> Deserializer<R> {
+    /// Reads the remaining messages, registering type definitions and skipping values
+    fn index_messages(&mut self, messages: &mut Vec<IndexEntry>) -> Result<()> {
+        let mut defined = Vec::new();
+
+        while let Some(type_id) = self.scan_message(&mut defined)? {
+            messages.push(IndexEntry {
+                offset: self.message_start,
+                len: self.offset() - self.message_start,
+                type_id: type_id.abs(),
+                definition: type_id < 0,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl<'de, R: Input<'de> + Seek> Deserializer<R> {
+    /// Moves to the n-th value of an index built from the same stream,
+    /// and makes all types defined by the stream known.
+    pub fn seek_value(&mut self, index: &Index, n: usize) -> Result<()> {
+        let message = match index.values.get(n) {
+            Some(&message) => message,
+            None => bail!(io::Error::new(io::ErrorKind::InvalidInput, format!("the index has no value {}", n))),
+        };
+
+        self.reader.seek(index.messages[message].offset)?;
+        self.types = index.types.clone();
+        self.messages = message as u64;
+        self.pending = None;
+
+        Ok(())
+    }
+}
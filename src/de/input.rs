@@ -0,0 +1,141 @@
+use std::cmp;
+use std::io::{self,Read,Seek,SeekFrom};
+#[cfg(feature = "memmap")]
+use memmap2::Mmap;
+use super::Deserializer;
+
+/// The source a `Deserializer` reads from.
+///
+/// This is implemented for every `io::Read`, and for [`SliceInput`],
+/// which holds the whole stream in memory and lets strings and byte slices
+/// be borrowed from it for `'de`.
+///
+/// Only a `SliceInput` can be borrowed from, so [`Deserializer::deserialize`]
+/// requires `DeserializeOwned` types when reading from an `io::Read`:
+///
+/// ```compile_fail
+/// # extern crate gob;
+/// # fn main() -> gob::Result<()> {
+/// let mut de = gob::Deserializer::new(std::io::Cursor::new(vec![]));
+/// let name: &str = de.deserialize()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Borrowing types passed to `serde::Deserialize::deserialize` directly
+/// fail with `ErrorKind::TypeMismatch` instead.
+///
+/// [`SliceInput`]: struct.SliceInput.html
+/// [`Deserializer::deserialize`]: struct.Deserializer.html#method.deserialize
+pub trait Input<'de> {
+    /// Reads into `buf`, like `io::Read::read`
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize>;
+
+    /// The unread rest of the input, if it's held in memory
+    fn remaining(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// Like `remaining`, but borrowed for `'de`
+    fn borrow_remaining(&self) -> Option<&'de [u8]> {
+        None
+    }
+
+    /// Marks the first `amt` bytes returned by `remaining` as read
+    fn advance(&mut self, _amt: usize) {}
+}
+
+impl<'de, R: Read> Input<'de> for R {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        Read::read(self, buf)
+    }
+}
+
+/// A gob stream held in memory, e.g. in a memory-mapped file.
+///
+/// Decoding from it needs no copies into an intermediate buffer,
+/// and `&'de str` or `&'de [u8]` fields borrow straight from the stream.
+/// Seeking is just moving a position, so random access through an `Index` is cheap.
+///
+/// Created by [`Deserializer::from_slice`](struct.Deserializer.html#method.from_slice).
+#[derive(Debug,Clone,Copy)]
+pub struct SliceInput<'de> {
+    bytes: &'de [u8],
+    pos: usize,
+}
+
+impl<'de> SliceInput<'de> {
+    pub fn new(bytes: &'de [u8]) -> Self {
+        SliceInput { bytes, pos: 0 }
+    }
+}
+
+impl<'de> Input<'de> for SliceInput<'de> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let rest = &self.bytes[self.pos..];
+        let n = cmp::min(rest.len(), buf.len());
+        buf[..n].copy_from_slice(&rest[..n]);
+        self.pos += n;
+        Ok(n)
+    }
+
+    fn remaining(&self) -> Option<&[u8]> {
+        self.borrow_remaining()
+    }
+
+    fn borrow_remaining(&self) -> Option<&'de [u8]> {
+        Some(&self.bytes[self.pos..])
+    }
+
+    fn advance(&mut self, amt: usize) {
+        self.pos = cmp::min(self.pos + amt, self.bytes.len());
+    }
+}
+
+impl<'de> Seek for SliceInput<'de> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => (self.bytes.len() as u64).checked_add_signed(offset),
+            SeekFrom::Current(offset) => (self.pos as u64).checked_add_signed(offset),
+        };
+
+        match pos {
+            Some(pos) => {
+                self.pos = cmp::min(pos, self.bytes.len() as u64) as usize;
+                Ok(self.pos as u64)
+            }
+            None => Err(io::Error::new(io::ErrorKind::InvalidInput, "seek to a negative position")),
+        }
+    }
+}
+
+impl<'de> Deserializer<SliceInput<'de>> {
+    /// Creates a deserializer decoding a stream held in memory.
+    /// Unlike with `new`, strings and byte slices can be borrowed from `bytes`.
+    pub fn from_slice(bytes: &'de [u8]) -> Self {
+        Self::new(SliceInput::new(bytes))
+    }
+
+    /// Creates a deserializer decoding a memory-mapped file.
+    /// Strings and byte slices can be borrowed from the mapping.
+    ///
+    /// ```no_run
+    /// # extern crate gob;
+    /// # extern crate memmap2;
+    /// # use std::fs::File;
+    /// # fn main() -> gob::Result<()> {
+    /// let file = File::open("archive.gob")?;
+    /// // Safe as long as no one modifies the file while it's mapped
+    /// let mmap = unsafe { memmap2::Mmap::map(&file)? };
+    ///
+    /// let mut de = gob::Deserializer::from_mmap(&mmap);
+    /// let name: &str = de.deserialize()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "memmap")]
+    pub fn from_mmap(mmap: &'de Mmap) -> Self {
+        Self::from_slice(mmap)
+    }
+}
@@ -1,29 +1,54 @@
-use std::io::Read;
+use std::sync::Arc;
 use serde;
 use serde::de::{Visitor,IntoDeserializer};
 use errors::*;
-use types::{TypeDef,WireTypeEnum};
-use super::ReadGob;
+use types::{TypeDef,Plan,FieldPlan};
+use super::{Deserializer,ReadGob,ValueDeserializer,Input};
 
-pub struct MapAccess<'a, R: Read + 'a> {
-    de: &'a mut super::Deserializer<R>,
+pub struct MapAccess<'a, R: 'a> {
+    de: &'a mut Deserializer<R>,
     type_def: TypeDef,
+    /// The decode plan of custom struct types
+    plan: Option<Arc<Plan>>,
     current_field: isize,
 }
 
-impl<'a, R: Read + 'a> MapAccess<'a, R> {
-    pub fn new(de: &'a mut super::Deserializer<R>, type_def: TypeDef) -> Self {
+impl<'a, 'de, R: Input<'de> + 'a> MapAccess<'a, R> {
+    pub fn new(de: &'a mut Deserializer<R>, type_def: TypeDef, plan: Option<Arc<Plan>>) -> Self {
         MapAccess {
             de,
             type_def,
+            plan,
             current_field: -1,
         }
     }
+
+    fn planned_field(&self) -> Result<&FieldPlan> {
+        let field_id = self.current_field as usize;
+        match self.plan.as_deref() {
+            Some(Plan::Struct(fields)) => fields.get(field_id).ok_or_else(|| Corruption::InvalidField(field_id).into()),
+            _ => Err(not_a_struct(&self.type_def).into()),
+        }
+    }
+
+    fn field_type(&self) -> Result<TypeDef> {
+        match self.plan {
+            Some(_) => self.planned_field().map(|field| field.type_def.clone()),
+            None => field_type(&self.type_def, self.current_field as usize),
+        }
+    }
+
+    fn field_name(&self) -> Result<&str> {
+        match self.type_def {
+            TypeDef::Custom(_) => Ok(&self.planned_field()?.name),
+            _ => field_name(&self.type_def, self.current_field as usize),
+        }
+    }
 }
 
 // `MapAccess` is provided to the `Visitor` to give it the ability to iterate
 // through entries of the map.
-impl<'a, 'de, R: Read> serde::de::MapAccess<'de> for MapAccess<'a, R> {
+impl<'a, 'de, R: Input<'de>> serde::de::MapAccess<'de> for MapAccess<'a, R> {
     type Error = Error;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
@@ -41,48 +66,7 @@ impl<'a, 'de, R: Read> serde::de::MapAccess<'de> for MapAccess<'a, R> {
             return Ok(None)
         }
         
-        let field_id = self.current_field;
-        let field_name = match self.type_def {
-              TypeDef::Bool
-            | TypeDef::Int
-            | TypeDef::Uint
-            | TypeDef::Float
-            | TypeDef::ByteSlice
-            | TypeDef::String
-            | TypeDef::Interface
-            | TypeDef::ArrayType
-            | TypeDef::Complex
-            | TypeDef::FieldTypeSlice
-            | TypeDef::MapType
-            | TypeDef::SliceType => bail!("Field name for {} not implemented", self.type_def.id()),
-            // TypeDef::MapType => visitor.visit_map(MapMap::new(de, ))
-            TypeDef::WireType => match field_id {
-                0 => "ArrayT",
-                1 => "SliceT",
-                2 => "StructT",
-                3 => "MapT",
-                _ => bail!(ErrorKind::InvalidField)
-            },
-            TypeDef::CommonType => match field_id {
-                0 => "Name",
-                1 => "Id",
-                _ => bail!(ErrorKind::InvalidField)
-            },
-            TypeDef::StructType => match field_id {
-                0 => "CommonType",
-                1 => "Field",
-                _ => bail!(ErrorKind::InvalidField)
-            },
-            TypeDef::FieldType => match field_id {
-                0 => "Name",
-                1 => "Id",
-                _ => bail!(ErrorKind::InvalidField)
-            },
-            TypeDef::Custom(ref wire_type) => match **wire_type {
-                WireTypeEnum::Struct(ref t) => t.fields().get(field_id as usize).map(|field| field.name()).ok_or(ErrorKind::InvalidField)?,
-                _ => bail!("Decoding of field name for {} not implemented")
-            }
-        };
+        let field_name = self.field_name()?;
 
         trace!("### FIELD NAME: {}", field_name);
 
@@ -93,48 +77,31 @@ impl<'a, 'de, R: Read> serde::de::MapAccess<'de> for MapAccess<'a, R> {
         where V: serde::de::DeserializeSeed<'de>
     {
         trace!("Next value");
-        seed.deserialize(self)
+        match seed.deserialize(&mut *self) {
+            Err(err) => match self.field_name() {
+                Ok(field_name) => Err(err.in_field(field_name)),
+                Err(_) => Err(err),
+            },
+            value => value,
+        }
     }
 }
 
-impl<'b, 'a, 'de, R: Read> serde::Deserializer<'de> for &'b mut MapAccess<'a, R> {
+impl<'b, 'a, 'de, R: Input<'de>> serde::Deserializer<'de> for &'b mut MapAccess<'a, R> {
    type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
         where V: Visitor<'de>
     {
-        let field_id = self.current_field;
-        match self.type_def.clone() {
-            TypeDef::WireType => match field_id {
-                2 => self.de.deserialize_value(visitor, TypeDef::StructType),
-                3 => self.de.deserialize_value(visitor, TypeDef::MapType),
-                _ => bail!("wireType field {} unimplemented", field_id),
-            },
-            TypeDef::StructType => match field_id {
-                0 => self.de.deserialize_value(visitor, TypeDef::CommonType),
-                1 => self.de.deserialize_value(visitor, TypeDef::FieldTypeSlice),
-                _ => bail!("structType field {} unimplemented", field_id),
-            },
-            TypeDef::FieldType => match field_id {
-                0 => self.de.deserialize_value(visitor, TypeDef::String),
-                1 => self.de.deserialize_value(visitor, TypeDef::Int), // TypeId
-                _ => bail!("fieldType field {} unimplemented", field_id),
-            },
-            TypeDef::CommonType => match field_id {
-                0 => self.de.deserialize_value(visitor, TypeDef::String),
-                1 => self.de.deserialize_value(visitor, TypeDef::Int), // TypeId
-                _ => bail!("commonType field {} unimplemented", field_id)
-            },
-            TypeDef::Custom(ref wire_type) => match **wire_type {
-                WireTypeEnum::Struct(ref t) => {
-                    let type_id = t.fields().get(field_id as usize).map(|field| field.id()).ok_or(ErrorKind::InvalidField)?;
-                    let type_def = self.de.look_up_type(type_id)?; // chain_err?
-                    self.de.deserialize_value(visitor, type_def)
-                },
-                _ => bail!("Decoding of field name for {} not implemented")
-            },
-            _ => bail!("Decoding of field value for id {} not implemented", self.type_def.id())
-        }
+        let type_def = self.field_type()?;
+        self.de.deserialize_value(visitor, type_def)
+    }
+
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+        where V: Visitor<'de>
+    {
+        let type_def = self.field_type()?;
+        serde::Deserializer::deserialize_newtype_struct(&mut ValueDeserializer::new(self.de, type_def), name, visitor)
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
@@ -145,7 +112,119 @@ impl<'b, 'a, 'de, R: Read> serde::Deserializer<'de> for &'b mut MapAccess<'a, R>
 
     forward_to_deserialize_any! {
         bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
-        byte_buf unit unit_struct newtype_struct seq tuple
+        byte_buf unit unit_struct seq tuple
         tuple_struct map struct enum identifier ignored_any
     }
 }
+
+/// Returns the name of a field of one of the builtin struct types describing types.
+/// Fields of custom structs are named by their decode plan instead.
+pub(super) fn field_name(type_def: &TypeDef, field_id: usize) -> Result<&'static str> {
+    Ok(match *type_def {
+          TypeDef::Bool
+        | TypeDef::Int
+        | TypeDef::Uint
+        | TypeDef::Float
+        | TypeDef::ByteSlice
+        | TypeDef::String
+        | TypeDef::Interface
+        | TypeDef::Complex
+        | TypeDef::FieldTypeSlice => bail!(not_a_struct(type_def)),
+        TypeDef::WireType => match field_id {
+            0 => "ArrayT",
+            1 => "SliceT",
+            2 => "StructT",
+            3 => "MapT",
+            4 => "GobEncoderT",
+            5 => "BinaryMarshalerT",
+            6 => "TextMarshalerT",
+            _ => bail!(Corruption::InvalidField(field_id))
+        },
+        TypeDef::CommonType => match field_id {
+            0 => "Name",
+            1 => "Id",
+            _ => bail!(Corruption::InvalidField(field_id))
+        },
+        TypeDef::ArrayType => match field_id {
+            0 => "CommonType",
+            1 => "Elem",
+            2 => "Len",
+            _ => bail!(Corruption::InvalidField(field_id))
+        },
+        TypeDef::SliceType => match field_id {
+            0 => "CommonType",
+            1 => "Elem",
+            _ => bail!(Corruption::InvalidField(field_id))
+        },
+        TypeDef::MapType => match field_id {
+            0 => "CommonType",
+            1 => "Key",
+            2 => "Elem",
+            _ => bail!(Corruption::InvalidField(field_id))
+        },
+        TypeDef::StructType => match field_id {
+            0 => "CommonType",
+            1 => "Field",
+            _ => bail!(Corruption::InvalidField(field_id))
+        },
+        TypeDef::FieldType => match field_id {
+            0 => "Name",
+            1 => "Id",
+            _ => bail!(Corruption::InvalidField(field_id))
+        },
+        TypeDef::Custom(ref wire_type) => bail!(ErrorKind::Unsupported(
+            format!("naming fields of type {} without its decode plan", wire_type.name()))),
+    })
+}
+
+/// Returns the type of a field of one of the builtin struct types describing types.
+/// Fields of custom structs are resolved by their decode plan instead.
+pub(super) fn field_type(type_def: &TypeDef, field_id: usize) -> Result<TypeDef> {
+    Ok(match *type_def {
+        TypeDef::WireType => match field_id {
+            0 => TypeDef::ArrayType,
+            1 => TypeDef::SliceType,
+            2 => TypeDef::StructType,
+            3 => TypeDef::MapType,
+            4..=6 => bail!(ErrorKind::Unsupported("types with custom GobEncoder or Marshaler implementations".into())),
+            _ => bail!(Corruption::InvalidField(field_id)),
+        },
+        TypeDef::ArrayType => match field_id {
+            0 => TypeDef::CommonType,
+            1 => TypeDef::Int, // TypeId
+            2 => TypeDef::Int,
+            _ => bail!(Corruption::InvalidField(field_id)),
+        },
+        TypeDef::SliceType => match field_id {
+            0 => TypeDef::CommonType,
+            1 => TypeDef::Int, // TypeId
+            _ => bail!(Corruption::InvalidField(field_id)),
+        },
+        TypeDef::MapType => match field_id {
+            0 => TypeDef::CommonType,
+            1 => TypeDef::Int, // TypeId
+            2 => TypeDef::Int, // TypeId
+            _ => bail!(Corruption::InvalidField(field_id)),
+        },
+        TypeDef::StructType => match field_id {
+            0 => TypeDef::CommonType,
+            1 => TypeDef::FieldTypeSlice,
+            _ => bail!(Corruption::InvalidField(field_id)),
+        },
+        TypeDef::FieldType => match field_id {
+            0 => TypeDef::String,
+            1 => TypeDef::Int, // TypeId
+            _ => bail!(Corruption::InvalidField(field_id)),
+        },
+        TypeDef::CommonType => match field_id {
+            0 => TypeDef::String,
+            1 => TypeDef::Int, // TypeId
+            _ => bail!(Corruption::InvalidField(field_id))
+        },
+        _ => bail!(not_a_struct(type_def))
+    })
+}
+
+fn not_a_struct(type_def: &TypeDef) -> ErrorKind {
+    ErrorKind::Unsupported(format!("decoding fields of non-struct type {}", type_def.name()))
+}
@@ -2,6 +2,32 @@
 mod deserializer;
 pub use self::deserializer::Deserializer;
 
+mod input;
+pub use self::input::{Input,SliceInput};
+
+mod options;
+pub use self::options::DeserializerOptions;
+
+mod checkpoint;
+pub use self::checkpoint::Checkpoint;
+
+mod index;
+pub use self::index::{Index,IndexEntry};
+
+mod stream;
+pub use self::stream::StreamDeserializer;
+
+mod parallel;
+pub use self::parallel::ParallelDeserializer;
+
+mod push;
+pub use self::push::{PushDecoder,Message};
+
+#[cfg(feature = "tokio")]
+mod async_deserializer;
+#[cfg(feature = "tokio")]
+pub use self::async_deserializer::{AsyncDeserializer,Next};
+
 mod read_gob;
 pub(self) use self::read_gob::ReadGob;
 
@@ -13,3 +39,21 @@ pub(self) use self::seq_access::SeqAccess;
 
 mod map_access;
 pub(self) use self::map_access::MapAccess;
+
+mod entry_access;
+pub(self) use self::entry_access::EntryAccess;
+
+mod frame;
+pub(crate) use self::frame::{FrameDecoder,FrameBuffer};
+
+mod counting_reader;
+pub(self) use self::counting_reader::CountingReader;
+
+mod skip;
+
+mod capture;
+
+mod dynamic;
+
+mod projected;
+pub(crate) use self::projected::ProjectionPlan;
@@ -0,0 +1,60 @@
+/// Limits applied while decoding, to protect against malicious or corrupt input.
+///
+/// The defaults are suitable for decoding data from untrusted peers.
+#[derive(Debug,Clone)]
+pub struct DeserializerOptions {
+    pub(crate) max_message_size: usize,
+    pub(crate) max_depth: usize,
+    pub(crate) max_elements: usize,
+    pub(crate) max_types: usize,
+    pub(crate) max_fields: usize,
+}
+
+impl DeserializerOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum size in bytes of a single message.
+    /// This also bounds the length of strings and byte slices.
+    pub fn max_message_size(mut self, max: usize) -> Self {
+        self.max_message_size = max;
+        self
+    }
+
+    /// Maximum nesting depth of structs, slices, arrays and maps.
+    pub fn max_depth(mut self, max: usize) -> Self {
+        self.max_depth = max;
+        self
+    }
+
+    /// Maximum number of elements in a single slice, array or map.
+    pub fn max_elements(mut self, max: usize) -> Self {
+        self.max_elements = max;
+        self
+    }
+
+    /// Maximum number of types a stream may define.
+    pub fn max_types(mut self, max: usize) -> Self {
+        self.max_types = max;
+        self
+    }
+
+    /// Maximum number of fields of a single struct type.
+    pub fn max_fields(mut self, max: usize) -> Self {
+        self.max_fields = max;
+        self
+    }
+}
+
+impl Default for DeserializerOptions {
+    fn default() -> Self {
+        DeserializerOptions {
+            max_message_size: 64 * 1024 * 1024,
+            max_depth: 128,
+            max_elements: 1024 * 1024,
+            max_types: 10_000,
+            max_fields: 1024,
+        }
+    }
+}
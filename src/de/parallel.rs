@@ -0,0 +1,263 @@
+use std::cmp;
+use std::collections::BTreeMap;
+use std::panic::{self,AssertUnwindSafe};
+use std::sync::{Arc,Mutex};
+use std::sync::mpsc::{self,Sender,Receiver};
+use std::thread::{self,JoinHandle};
+use std::vec;
+use serde::de::DeserializeOwned;
+use errors::*;
+use types::TypeMap;
+use super::{Deserializer,DeserializerOptions,Input,SliceInput};
+
+/// Values are handed to the workers in batches of consecutive messages, up to these sizes
+const BATCH_VALUES: usize = 256;
+const BATCH_BYTES: u64 = 64 * 1024;
+
+/// Number of batches decoded ahead per thread, bounding the memory held by pending values
+const BATCHES_PER_THREAD: u64 = 4;
+
+type Outcome<T> = thread::Result<Vec<Result<T>>>;
+
+/// Iterator decoding the values of a gob stream on several threads.
+///
+/// Created by [`Deserializer::into_parallel_iter`](struct.Deserializer.html#method.into_parallel_iter).
+/// The stream is split into messages and its type definitions are applied on the calling thread,
+/// while values are decoded by a pool of worker threads.
+/// Values are returned in stream order.
+/// Iteration ends cleanly when the stream ends at a message boundary
+/// and stops after the first error.
+///
+/// If decoding a value panics, the panic is resumed on the calling thread.
+pub struct ParallelDeserializer<R, T> {
+    de: Deserializer<R>,
+    jobs: Option<Sender<Job>>,
+    results: Receiver<(u64, Outcome<T>)>,
+    workers: Vec<JoinHandle<()>>,
+    /// Batches that were decoded before their turn
+    ready: BTreeMap<u64, Outcome<T>>,
+    /// The batch whose values are being returned
+    current: vec::IntoIter<Result<T>>,
+    /// Number of batches handed to the workers
+    sent: u64,
+    /// Number of batches taken from the workers
+    received: u64,
+    max_in_flight: u64,
+    /// Set once no more batches will be sent, along with the error that stopped the scan, if any
+    scanned: Option<Option<Error>>,
+    failed: bool,
+}
+
+/// A run of consecutive messages holding at least one value,
+/// along with everything needed to decode them on their own
+struct Job {
+    seq: u64,
+    offset: u64,
+    message: u64,
+    bytes: Vec<u8>,
+    values: usize,
+    types: TypeMap,
+}
+
+impl<'de, R, T> ParallelDeserializer<R, T>
+    where R: Input<'de>, T: DeserializeOwned + Send + 'static
+{
+    pub(super) fn new(de: Deserializer<R>, threads: usize) -> Self {
+        let threads = cmp::max(threads, 1);
+        let (jobs, job_queue) = mpsc::channel();
+        let job_queue = Arc::new(Mutex::new(job_queue));
+        let (result_sender, results) = mpsc::channel();
+
+        let workers = (0..threads)
+            .map(|_| {
+                let job_queue = job_queue.clone();
+                let results = result_sender.clone();
+                let options = de.options().clone();
+                thread::spawn(move || work(&job_queue, &results, &options))
+            })
+            .collect();
+
+        ParallelDeserializer {
+            de,
+            jobs: Some(jobs),
+            results,
+            workers,
+            ready: BTreeMap::new(),
+            current: Vec::new().into_iter(),
+            sent: 0,
+            received: 0,
+            max_in_flight: threads as u64 * BATCHES_PER_THREAD,
+            scanned: None,
+            failed: false,
+        }
+    }
+
+    /// Hands batches to the workers until enough of them are in flight
+    fn send_jobs(&mut self) {
+        while self.scanned.is_none() && self.sent - self.received < self.max_in_flight {
+            let job = self.read_job();
+
+            if job.values == 0 {
+                continue;
+            }
+
+            if let Some(ref jobs) = self.jobs {
+                jobs.send(job).expect("decoding threads exited early");
+            }
+
+            self.sent += 1;
+        }
+    }
+
+    /// Reads the next batch of messages. Definitions are registered on the way,
+    /// so that the following batches can be decoded independently of this one.
+    fn read_job(&mut self) -> Job {
+        let offset = self.de.offset();
+        let message = self.de.messages;
+        let types = self.de.type_map();
+        let mut defined = Vec::new();
+        let mut values = 0;
+        let mut len = 0;
+
+        self.de.reader.start_recording();
+
+        while values < BATCH_VALUES && len < BATCH_BYTES {
+            match self.de.scan_message(&mut defined) {
+                Ok(Some(type_id)) => if type_id >= 0 {
+                    values += 1;
+                    len = self.de.offset() - offset;
+                },
+                Ok(None) => {
+                    self.scanned = Some(None);
+                    break;
+                }
+                Err(err) => {
+                    self.scanned = Some(Some(err.at(self.de.offset(), self.de.messages, None)));
+                    break;
+                }
+            }
+        }
+
+        // Drop anything following the last value, like a partial message at the end of the stream
+        let mut bytes = self.de.reader.stop_recording();
+        bytes.truncate(len as usize);
+
+        Job {
+            seq: self.sent,
+            offset,
+            message,
+            bytes,
+            values,
+            types,
+        }
+    }
+}
+
+impl<'de, R, T> Iterator for ParallelDeserializer<R, T>
+    where R: Input<'de>, T: DeserializeOwned + Send + 'static
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        loop {
+            if self.failed {
+                return None;
+            }
+
+            if let Some(value) = self.current.next() {
+                self.failed = value.is_err();
+                return Some(value);
+            }
+
+            self.send_jobs();
+
+            // Nothing is in flight, so the scan has ended
+            if self.received == self.sent {
+                self.failed = true;
+                return self.scanned.take().and_then(|err| err).map(Err);
+            }
+
+            let outcome = loop {
+                if let Some(outcome) = self.ready.remove(&self.received) {
+                    break outcome;
+                }
+
+                let (seq, outcome) = self.results.recv().expect("decoding threads exited early");
+                self.ready.insert(seq, outcome);
+            };
+
+            self.received += 1;
+
+            match outcome {
+                Ok(values) => self.current = values.into_iter(),
+                Err(payload) => panic::resume_unwind(payload),
+            }
+        }
+    }
+}
+
+impl<R, T> Drop for ParallelDeserializer<R, T> {
+    fn drop(&mut self) {
+        // Closing the queue lets the workers finish
+        self.jobs = None;
+
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn work<T: DeserializeOwned>(
+    job_queue: &Mutex<Receiver<Job>>,
+    results: &Sender<(u64, Outcome<T>)>,
+    options: &DeserializerOptions,
+) {
+    loop {
+        let job = job_queue.lock().expect("job queue poisoned").recv();
+        let job = match job {
+            Ok(job) => job,
+            Err(_) => return,
+        };
+
+        let seq = job.seq;
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| job.decode(options)));
+
+        if results.send((seq, outcome)).is_err() {
+            return;
+        }
+    }
+}
+
+impl Job {
+    /// Decodes the values of the batch, stopping at the first error
+    fn decode<T: DeserializeOwned>(self, options: &DeserializerOptions) -> Vec<Result<T>> {
+        let input = SliceInput::new(&self.bytes);
+        let mut de = Deserializer::starting_at(input, options.clone(), self.types, self.offset, self.message);
+
+        let mut values = Vec::with_capacity(self.values);
+
+        for _ in 0..self.values {
+            let value = de.deserialize();
+            let failed = value.is_err();
+            values.push(value);
+
+            if failed {
+                break;
+            }
+        }
+
+        values
+    }
+}
+
+impl<'de, R: Input<'de>> Deserializer<R> {
+    /// Returns an iterator decoding the remaining values of the stream on `threads` threads.
+    ///
+    /// This pays off for long streams of values that are expensive to decode,
+    /// e.g. when reprocessing archived files.
+    pub fn into_parallel_iter<T>(self, threads: usize) -> ParallelDeserializer<R, T>
+        where T: DeserializeOwned + Send + 'static
+    {
+        ParallelDeserializer::new(self, threads)
+    }
+}
@@ -0,0 +1,331 @@
+use std::io::Read;
+use std::sync::Arc;
+use serde;
+use serde::de::{Deserialize,DeserializeOwned,Visitor,IntoDeserializer};
+use errors::*;
+use projection::{Projection,Selection};
+use types::{TypeDef,WireTypeEnum};
+use super::{Deserializer,ValueDeserializer,ReadGob,Input,SliceInput};
+
+/// A projection resolved against the type of a value
+#[derive(Debug)]
+pub(crate) enum ProjectionPlan {
+    /// Decode the whole value
+    All,
+    /// Decode the selected fields of a struct and skip the others, indexed by field number
+    Struct(Vec<ProjectedField>),
+    /// Project each element of a slice or array
+    Elements(TypeDef, Box<ProjectionPlan>),
+    /// Project each value of a map, decoding the keys in full
+    Entries(TypeDef, TypeDef, Box<ProjectionPlan>),
+}
+
+#[derive(Debug)]
+pub(crate) struct ProjectedField {
+    name: String,
+    type_def: TypeDef,
+    /// `None` if the field is skipped
+    plan: Option<ProjectionPlan>,
+}
+
+/// Values decoded from an `io::Read` can't borrow from it, so they must be owned
+impl<R: Read> Deserializer<R> {
+    /// Deserializes the next value, decoding only the fields selected by `projection`.
+    /// All other fields are skipped without being decoded.
+    ///
+    /// ```compile_fail
+    /// # extern crate gob;
+    /// # fn main() -> gob::Result<()> {
+    /// let projection = gob::Projection::new(&["Name"])?;
+    /// let mut de = gob::Deserializer::new(std::io::Cursor::new(vec![]));
+    /// let name: &str = de.deserialize_projected(&projection)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn deserialize_projected<T: DeserializeOwned>(&mut self, projection: &Projection) -> Result<T> {
+        self.deserialize_projected_next(projection)
+    }
+}
+
+/// Values decoded from memory may borrow strings and byte slices from it
+impl<'de> Deserializer<SliceInput<'de>> {
+    /// Deserializes the next value, decoding only the fields selected by `projection`.
+    /// All other fields are skipped without being decoded.
+    pub fn deserialize_projected<T: Deserialize<'de>>(&mut self, projection: &Projection) -> Result<T> {
+        self.deserialize_projected_next(projection)
+    }
+}
+
+impl<'de, R: Input<'de>> Deserializer<R> {
+    fn deserialize_projected_next<T: Deserialize<'de>>(&mut self, projection: &Projection) -> Result<T> {
+        self.deserialize_message(|de, type_def| {
+            let plan = de.projection_plan(projection, &type_def)?;
+            T::deserialize(&mut ProjectedDeserializer { de, type_def: &type_def, plan: &plan })
+        })
+    }
+
+    /// Resolves `projection` against the type of a value.
+    /// Plans for custom types are compiled once and kept by the projection.
+    fn projection_plan(&self, projection: &Projection, type_def: &TypeDef) -> Result<Arc<ProjectionPlan>> {
+        let wire_type = match *type_def {
+            TypeDef::Custom(ref wire_type) => wire_type,
+            _ => return self.compile(projection.selection(), type_def, "").map(Arc::new),
+        };
+
+        if let Some(plan) = projection.cached_plan(wire_type) {
+            return Ok(plan);
+        }
+
+        let plan = Arc::new(self.compile(projection.selection(), type_def, "")?);
+        projection.cache_plan(wire_type.clone(), plan.clone());
+        Ok(plan)
+    }
+
+    fn compile(&self, selection: &Selection, type_def: &TypeDef, path: &str) -> Result<ProjectionPlan> {
+        if selection.all {
+            return Ok(ProjectionPlan::All);
+        }
+
+        let wire_type = match *type_def {
+            TypeDef::Custom(ref wire_type) => wire_type.clone(),
+            _ => bail!(not_projectable(path, type_def)),
+        };
+
+        if let Some(ref elements) = selection.elements {
+            let path = format!("{}[]", path);
+
+            return match *wire_type {
+                WireTypeEnum::Slice(ref t) => {
+                    let elem_def = self.look_up_type(t.elem())?;
+                    let plan = self.compile(elements, &elem_def, &path)?;
+                    Ok(ProjectionPlan::Elements(elem_def, Box::new(plan)))
+                }
+                WireTypeEnum::Array(ref t) => {
+                    let elem_def = self.look_up_type(t.elem())?;
+                    let plan = self.compile(elements, &elem_def, &path)?;
+                    Ok(ProjectionPlan::Elements(elem_def, Box::new(plan)))
+                }
+                WireTypeEnum::Map(ref t) => {
+                    let key_def = self.look_up_type(t.key())?;
+                    let elem_def = self.look_up_type(t.elem())?;
+                    let plan = self.compile(elements, &elem_def, &path)?;
+                    Ok(ProjectionPlan::Entries(key_def, elem_def, Box::new(plan)))
+                }
+                WireTypeEnum::Struct(_) => bail!(ErrorKind::InvalidProjection(
+                    format!("'{}' has struct type {}, which has no elements", path, type_def.name())
+                )),
+            };
+        }
+
+        let t = match *wire_type {
+            WireTypeEnum::Struct(ref t) => t,
+            _ => bail!(not_projectable(path, type_def)),
+        };
+
+        if let Some(name) = selection.fields.keys().find(|name| !t.fields().iter().any(|field| field.name() == *name)) {
+            bail!(ErrorKind::InvalidProjection(format!("type {} has no field '{}'", type_def.name(), name)))
+        }
+
+        let fields = t.fields().iter()
+            .map(|field| {
+                let type_def = self.look_up_type(field.id())?;
+                let plan = match selection.fields.get(field.name()) {
+                    Some(selection) => {
+                        let path = if path.is_empty() { field.name().into() } else { format!("{}.{}", path, field.name()) };
+                        Some(self.compile(selection, &type_def, &path)?)
+                    }
+                    None => None,
+                };
+
+                Ok(ProjectedField { name: field.name().into(), type_def, plan })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(ProjectionPlan::Struct(fields))
+    }
+}
+
+fn not_projectable(path: &str, type_def: &TypeDef) -> ErrorKind {
+    let path = if path.is_empty() { "the value" } else { path };
+    ErrorKind::InvalidProjection(format!("{} has type {}, which has no fields", path, type_def.name()))
+}
+
+struct ProjectedDeserializer<'a, 'p, R: 'a> {
+    de: &'a mut Deserializer<R>,
+    type_def: &'p TypeDef,
+    plan: &'p ProjectionPlan,
+}
+
+impl<'a, 'b, 'p, 'de, R: Input<'de>> serde::Deserializer<'de> for &'b mut ProjectedDeserializer<'a, 'p, R> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+        where V: Visitor<'de>
+    {
+        let plan = self.plan;
+        let result = match *plan {
+            ProjectionPlan::All => self.de.deserialize_value(visitor, self.type_def.clone()),
+            ProjectionPlan::Struct(ref fields) => self.de.nested(|de| visitor.visit_map(ProjectedFields {
+                de,
+                fields,
+                current_field: -1,
+            })),
+            ProjectionPlan::Elements(ref elem_def, ref plan) => self.de.nested(|de| {
+                let len = de.read_len()?;
+                visitor.visit_seq(ProjectedElements { de, len, current_index: 0, elem_def, plan })
+            }),
+            ProjectionPlan::Entries(ref key_def, ref elem_def, ref plan) => self.de.nested(|de| {
+                let len = de.read_len()?;
+                visitor.visit_map(ProjectedEntries { de, len, current_index: 0, key_def, elem_def, plan })
+            }),
+        };
+
+        result.map_err(|err| err.in_type(self.type_def.name()))
+    }
+
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+        where V: Visitor<'de>
+    {
+        match *self.plan {
+            ProjectionPlan::All => serde::Deserializer::deserialize_newtype_struct(
+                &mut ValueDeserializer::new(self.de, self.type_def.clone()), name, visitor
+            ),
+            _ => visitor.visit_newtype_struct(self),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+        where V: Visitor<'de>
+    {
+        visitor.visit_some(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
+        byte_buf unit unit_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Presents the selected fields of a struct, skipping the others
+struct ProjectedFields<'a, 'p, R: 'a> {
+    de: &'a mut Deserializer<R>,
+    fields: &'p [ProjectedField],
+    current_field: isize,
+}
+
+impl<'a, 'p, 'de, R: Input<'de>> ProjectedFields<'a, 'p, R> {
+    fn current(&self) -> Result<&'p ProjectedField> {
+        let field_id = self.current_field as usize;
+        self.fields.get(field_id).ok_or_else(|| Corruption::InvalidField(field_id).into())
+    }
+}
+
+impl<'a, 'p, 'de, R: Input<'de>> serde::de::MapAccess<'de> for ProjectedFields<'a, 'p, R> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+        where K: serde::de::DeserializeSeed<'de>
+    {
+        loop {
+            let field_increment = self.de.reader().read_gob_usize()?;
+            if field_increment == 0 {
+                return Ok(None);
+            }
+
+            self.current_field += field_increment as isize;
+            let field = self.current()?;
+
+            if field.plan.is_some() {
+                return seed.deserialize(field.name.as_str().into_deserializer()).map(Some);
+            }
+
+            self.de.skip_value(&field.type_def, &mut Vec::new())
+                .map_err(|err| err.in_field(&field.name))?;
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+        where V: serde::de::DeserializeSeed<'de>
+    {
+        let field = self.current()?;
+        let plan = match field.plan {
+            Some(ref plan) => plan,
+            None => return Err(Corruption::InvalidField(self.current_field as usize).into()),
+        };
+
+        let de = &mut ProjectedDeserializer { de: self.de, type_def: &field.type_def, plan };
+        seed.deserialize(de).map_err(|err| err.in_field(&field.name))
+    }
+}
+
+/// Presents the projected elements of a slice or array
+struct ProjectedElements<'a, 'p, R: 'a> {
+    de: &'a mut Deserializer<R>,
+    len: usize,
+    current_index: usize,
+    elem_def: &'p TypeDef,
+    plan: &'p ProjectionPlan,
+}
+
+impl<'a, 'p, 'de, R: Input<'de>> serde::de::SeqAccess<'de> for ProjectedElements<'a, 'p, R> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+        where T: serde::de::DeserializeSeed<'de>
+    {
+        if self.current_index >= self.len {
+            return Ok(None);
+        }
+
+        self.current_index += 1;
+
+        let index = self.current_index - 1;
+        let de = &mut ProjectedDeserializer { de: self.de, type_def: self.elem_def, plan: self.plan };
+        seed.deserialize(de).map(Some).map_err(|err| err.in_element(index))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.current_index)
+    }
+}
+
+/// Presents the entries of a map with projected values
+struct ProjectedEntries<'a, 'p, R: 'a> {
+    de: &'a mut Deserializer<R>,
+    len: usize,
+    current_index: usize,
+    key_def: &'p TypeDef,
+    elem_def: &'p TypeDef,
+    plan: &'p ProjectionPlan,
+}
+
+impl<'a, 'p, 'de, R: Input<'de>> serde::de::MapAccess<'de> for ProjectedEntries<'a, 'p, R> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+        where K: serde::de::DeserializeSeed<'de>
+    {
+        if self.current_index >= self.len {
+            return Ok(None);
+        }
+
+        self.current_index += 1;
+
+        let index = self.current_index - 1;
+        let de = &mut ValueDeserializer::new(self.de, self.key_def.clone());
+        seed.deserialize(de).map(Some).map_err(|err| err.in_element(index))
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+        where V: serde::de::DeserializeSeed<'de>
+    {
+        let index = self.current_index - 1;
+        let de = &mut ProjectedDeserializer { de: self.de, type_def: self.elem_def, plan: self.plan };
+        seed.deserialize(de).map_err(|err| err.in_element(index))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.current_index)
+    }
+}
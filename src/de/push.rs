@@ -0,0 +1,170 @@
+use std::collections::VecDeque;
+use serde::de::DeserializeOwned;
+use errors::*;
+use schema::TypeSchema;
+use types::{TypeId,TypeMap};
+use super::{DeserializerOptions,FrameDecoder,FrameBuffer};
+
+/// Decodes a gob stream from bytes pushed into it, without doing any I/O itself.
+///
+/// This suits event loops and callbacks handing over data as it arrives:
+/// bytes are buffered with [`feed`], and [`poll_message`] returns
+/// whatever has arrived completely since the last call.
+/// Values are only decoded once all of their messages are buffered,
+/// so input can be fed in pieces of any size.
+///
+/// ```
+/// # extern crate gob;
+/// # use gob::{Message,PushDecoder};
+/// # fn main() -> gob::Result<()> {
+/// // The int 3, arriving in two pieces
+/// let chunks: Vec<&[u8]> = vec![&[3, 4], &[0, 6]];
+/// let mut decoder = PushDecoder::<i64>::new();
+///
+/// for chunk in chunks {
+///     decoder.feed(chunk);
+///
+///     while let Some(message) = decoder.poll_message()? {
+///         match message {
+///             Message::Definition(schema) => println!("new type {}", schema.name()),
+///             Message::Value(n) => println!("{}", n),
+///         }
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`feed`]: #method.feed
+/// [`poll_message`]: #method.poll_message
+pub struct PushDecoder<T> {
+    /// Bytes fed but not decoded yet, starting at a message boundary
+    buf: FrameBuffer,
+    finished: bool,
+    frames: FrameDecoder,
+    /// Types defined in front of the last value decoded, not returned yet
+    defined: VecDeque<TypeId>,
+    /// The last value decoded, returned after its definitions
+    value: Option<Result<T>>,
+}
+
+/// What a `PushDecoder` has decoded
+#[derive(Debug,Clone,PartialEq)]
+pub enum Message<T> {
+    /// A type definition, which has been applied to the stream
+    Definition(TypeSchema),
+    Value(T),
+}
+
+impl<T: DeserializeOwned> PushDecoder<T> {
+    pub fn new() -> Self {
+        Self::with_options(DeserializerOptions::default())
+    }
+
+    pub fn with_options(options: DeserializerOptions) -> Self {
+        PushDecoder {
+            buf: FrameBuffer::new(),
+            finished: false,
+            frames: FrameDecoder::new(options),
+            defined: VecDeque::new(),
+            value: None,
+        }
+    }
+
+    pub fn options(&self) -> &DeserializerOptions {
+        &self.frames.options
+    }
+
+    /// Returns a snapshot of the types defined so far
+    pub fn type_map(&self) -> TypeMap {
+        self.frames.types.clone()
+    }
+
+    /// Replaces the known types, like `Deserializer::set_type_map`
+    pub fn set_type_map(&mut self, types: TypeMap) {
+        self.frames.types = types;
+    }
+
+    /// Offset of the first byte that hasn't been decoded yet
+    pub fn offset(&self) -> u64 {
+        self.frames.offset
+    }
+
+    /// Number of bytes fed but not decoded yet
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Buffers the next bytes of the stream
+    pub fn feed(&mut self, bytes: &[u8]) {
+        debug_assert!(!self.finished, "input fed after the end of the stream");
+        self.buf.extend(bytes);
+    }
+
+    /// Marks the end of the stream.
+    ///
+    /// Type definitions at the end of the stream, which aren't followed by a value,
+    /// are then returned by `poll_message`, and a partial message left in the buffer is an error.
+    pub fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    /// Returns the next type definition or value that has arrived completely,
+    /// or `None` if more input is needed.
+    ///
+    /// The definitions in front of a value are returned before it.
+    /// A value failing to decode is skipped after returning its error.
+    ///
+    /// Errors in the framing of the stream, like a message exceeding `max_message_size`,
+    /// are terminal: the end of the offending message can't be trusted,
+    /// so the same error is returned by every later call.
+    pub fn poll_message(&mut self) -> Result<Option<Message<T>>> {
+        loop {
+            if let Some(type_id) = self.defined.pop_front() {
+                let type_def = self.frames.types.get(type_id)
+                    .ok_or(Corruption::UndefinedType(type_id))?;
+                return Ok(Some(Message::Definition(TypeSchema::new(type_def))));
+            }
+
+            if let Some(value) = self.value.take() {
+                return value.map(|value| Some(Message::Value(value)));
+            }
+
+            if !self.decode_frame()? {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Decodes the next frame if it's complete, or the rest of the stream after `finish`.
+    /// Returns false if there's nothing to decode.
+    fn decode_frame(&mut self) -> Result<bool> {
+        let mut defined = Vec::new();
+
+        let len = match self.frames.scan_frame(self.buf.pending(), &mut defined)? {
+            Some(len) => len,
+            None if self.finished && !self.buf.is_empty() => self.buf.len(),
+            None => return Ok(false),
+        };
+
+        let (applied, value) = self.frames.decode_frame(&self.buf.pending()[..len]);
+        self.buf.consume(len);
+
+        if applied {
+            self.defined.extend(defined);
+        }
+
+        self.value = match value {
+            Ok(value) => value.map(Ok),
+            Err(err) => Some(Err(err)),
+        };
+
+        Ok(true)
+    }
+}
+
+impl<T: DeserializeOwned> Default for PushDecoder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
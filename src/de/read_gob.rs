@@ -1,12 +1,48 @@
-use std::io::Read;
+use std::cmp;
+use std::io::{self,Read,BufRead};
 use std::mem::{self,size_of};
 use byteorder::ReadBytesExt;
 use byteorder::BigEndian as BE;
 use errors::*;
 use types::TypeId;
 
-pub trait ReadGob: Read {
+const MAX_PREALLOC: usize = 64 * 1024;
+
+/// Parses an unsigned number of at most `max_bytes` bytes from the start of `buf`.
+/// Returns the number and its encoded length, or `None` if `buf` doesn't hold all of it.
+pub(super) fn parse_buffered_uint(buf: &[u8], max_bytes: usize) -> Result<Option<(u64, usize)>> {
+    let byte = match buf.first() {
+        Some(&byte) => byte as i8,
+        None => return Ok(None),
+    };
+
+    if byte >= 0 {
+        return Ok(Some((byte as u64, 1)));
+    }
+
+    let n_bytes = (-(byte as i16)) as usize;
+
+    if n_bytes > max_bytes {
+        bail!(Corruption::NumOutOfRange);
+    }
+
+    let bytes = match buf.get(1..1 + n_bytes) {
+        Some(bytes) => bytes,
+        None => return Ok(None),
+    };
+
+    let n = bytes.iter().fold(0, |n, &byte| n << 8 | byte as u64);
+
+    Ok(Some((n, 1 + n_bytes)))
+}
+
+pub trait ReadGob: BufRead {
     fn read_gob_u64(&mut self) -> Result<u64> {
+        if let Some((n, len)) = parse_buffered_uint(self.fill_buf()?, size_of::<u64>())? {
+            self.consume(len);
+            return Ok(n);
+        }
+
         let byte = self.read_i8()?;
 
         if byte >= 0 {
@@ -16,11 +52,11 @@ pub trait ReadGob: Read {
         let n_bytes = (-byte) as usize;
 
         if n_bytes == 0 {
-            bail!(ErrorKind::NumZeroBytes);
+            bail!(Corruption::NumZeroBytes);
         }
 
         if n_bytes > size_of::<u64>() {
-            bail!(ErrorKind::NumOutOfRange);
+            bail!(Corruption::NumOutOfRange);
         }
 
         let bytes = self.read_uint::<BE>(n_bytes)?;
@@ -29,6 +65,11 @@ pub trait ReadGob: Read {
     }
 
     fn read_gob_usize(&mut self) -> Result<usize> {
+        if let Some((n, len)) = parse_buffered_uint(self.fill_buf()?, cmp::min(size_of::<usize>(), size_of::<u64>()))? {
+            self.consume(len);
+            return Ok(n as usize);
+        }
+
         let byte = self.read_i8()?;
 
         if byte >= 0 {
@@ -38,11 +79,11 @@ pub trait ReadGob: Read {
         let n_bytes = (-byte) as usize;
 
         if n_bytes == 0 {
-            bail!(ErrorKind::NumZeroBytes);
+            bail!(Corruption::NumZeroBytes);
         }
 
         if n_bytes > size_of::<usize>() || n_bytes > size_of::<u64>() {
-            bail!(ErrorKind::NumOutOfRange);
+            bail!(Corruption::NumOutOfRange);
         }
 
         let bytes = self.read_uint::<BE>(n_bytes)?;
@@ -74,16 +115,45 @@ pub trait ReadGob: Read {
         }
     }
 
-    fn read_gob_bytes(&mut self) -> Result<Vec<u8>> {
-        let len = self.read_gob_usize()?;
-        // TODO: Allow setting maximum length to avoid malicious OOM
-        let mut r = self.take(len as u64); // TODO: Fix cast
-        let mut data = Vec::with_capacity(len);
-        r.read_to_end(&mut data)?;
+    /// Reads exactly `len` raw bytes
+    fn read_gob_exact(&mut self, len: usize) -> Result<Vec<u8>> {
+        let buffered = {
+            let buf = self.fill_buf()?;
+            buf.get(..len).map(<[u8]>::to_vec)
+        };
+
+        if let Some(data) = buffered {
+            self.consume(len);
+            return Ok(data);
+        }
+
+        // The length is untrusted, so let the buffer grow with the data actually read
+        let mut data = Vec::with_capacity(cmp::min(len, MAX_PREALLOC));
+        self.take(len as u64).read_to_end(&mut data)?;
+
+        if data.len() != len {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
 
         Ok(data)
     }
 
+    /// Consumes exactly `len` raw bytes without copying them
+    fn skip_gob_exact(&mut self, mut len: usize) -> Result<()> {
+        while len > 0 {
+            let n = cmp::min(self.fill_buf()?.len(), len);
+
+            if n == 0 {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+            }
+
+            self.consume(n);
+            len -= n;
+        }
+
+        Ok(())
+    }
+
     fn read_gob_f64(&mut self) -> Result<f64> {
         unsafe {
             let float: u64 = self.read_gob_u64()?;
@@ -101,4 +171,4 @@ pub trait ReadGob: Read {
     }
 }
 
-impl<R: Read> ReadGob for R {}
+impl<R: BufRead> ReadGob for R {}
@@ -1,19 +1,23 @@
-use std::io::Read;
 use serde;
 use errors::*;
 use types::TypeDef;
-use super::{Deserializer,ValueDeserializer,ReadGob};
+use super::{Deserializer,ValueDeserializer,Input};
 
-pub struct SeqAccess<'a, R: Read + 'a> {
+pub struct SeqAccess<'a, R: 'a> {
     de: &'a mut Deserializer<R>,
     len: usize,
     current_index: usize,
     type_def: TypeDef,
 }
 
-impl<'a, R: Read + 'a> SeqAccess<'a, R> {
+impl<'a, 'de, R: Input<'de> + 'a> SeqAccess<'a, R> {
     pub fn new(de: &'a mut Deserializer<R>, type_def: TypeDef) -> Result<Self> {
-        let len = de.reader().read_gob_usize()?;
+        let len = match type_def {
+            // The fields of a struct type being defined
+            TypeDef::FieldType => de.read_field_count()?,
+            _ => de.read_len()?,
+        };
+
         Ok(SeqAccess {
             de,
             len,
@@ -23,7 +27,7 @@ impl<'a, R: Read + 'a> SeqAccess<'a, R> {
     }
 }
 
-impl<'a, 'de, R: Read> serde::de::SeqAccess<'de> for SeqAccess<'a, R> {
+impl<'a, 'de, R: Input<'de>> serde::de::SeqAccess<'de> for SeqAccess<'a, R> {
     type Error = Error;
 
     fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
@@ -33,9 +37,14 @@ impl<'a, 'de, R: Read> serde::de::SeqAccess<'de> for SeqAccess<'a, R> {
             return Ok(None);
         }
 
+        let index = self.current_index;
         self.current_index += 1;
 
-        let ref mut de = ValueDeserializer::new(self.de, self.type_def.clone());
-        seed.deserialize(de).map(Some)
+        let de = &mut ValueDeserializer::new(self.de, self.type_def.clone());
+        seed.deserialize(de).map(Some).map_err(|err| err.in_element(index))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.current_index)
     }
 }
@@ -0,0 +1,158 @@
+use errors::*;
+use types::{TypeId,TypeDef,Plan,FieldPlan};
+use super::{Deserializer,ReadGob,Input};
+use super::map_access::field_type;
+
+impl<'de, R: Input<'de>> Deserializer<R> {
+    /// Reads past a value of the given type without decoding it.
+    /// The concrete types of interface values on the way are added to `interface_types`.
+    pub(super) fn skip_value(&mut self, type_def: &TypeDef, interface_types: &mut Vec<TypeId>) -> Result<()> {
+        match *type_def {
+            TypeDef::Bool
+            | TypeDef::Int
+            | TypeDef::Uint
+            | TypeDef::Float => {
+                self.reader.read_gob_u64()?;
+            }
+            TypeDef::Complex => {
+                self.reader.read_gob_u64()?;
+                self.reader.read_gob_u64()?;
+            }
+            TypeDef::ByteSlice
+            | TypeDef::String => {
+                let len = self.reader.read_gob_usize()?;
+                self.skip_bytes(len)?;
+            }
+            TypeDef::Interface => self.skip_interface(interface_types)?,
+            TypeDef::FieldTypeSlice => self.skip_elements(&TypeDef::FieldType, interface_types)?,
+            TypeDef::Custom(ref wire_type) => match *self.plan(wire_type.id())? {
+                Plan::Struct(ref fields) => self.skip_fields(fields, interface_types)?,
+                Plan::Elements(ref elem_def) => self.skip_elements(elem_def, interface_types)?,
+                Plan::Entries(ref key_def, ref elem_def) => self.skip_entries(key_def, elem_def, interface_types)?,
+            },
+            TypeDef::WireType
+            | TypeDef::ArrayType
+            | TypeDef::CommonType
+            | TypeDef::SliceType
+            | TypeDef::StructType
+            | TypeDef::FieldType
+            | TypeDef::MapType => self.skip_builtin_struct(type_def, interface_types)?,
+        }
+
+        Ok(())
+    }
+
+    fn skip_builtin_struct(&mut self, type_def: &TypeDef, interface_types: &mut Vec<TypeId>) -> Result<()> {
+        self.nested(|de| {
+            let mut current_field = -1isize;
+
+            loop {
+                let field_increment = de.reader.read_gob_usize()?;
+                if field_increment == 0 {
+                    return Ok(());
+                }
+
+                current_field += field_increment as isize;
+                let field_def = field_type(type_def, current_field as usize)?;
+                de.skip_value(&field_def, interface_types)?;
+            }
+        })
+    }
+
+    fn skip_fields(&mut self, fields: &[FieldPlan], interface_types: &mut Vec<TypeId>) -> Result<()> {
+        self.nested(|de| {
+            let mut current_field = -1isize;
+
+            loop {
+                let field_increment = de.reader.read_gob_usize()?;
+                if field_increment == 0 {
+                    return Ok(());
+                }
+
+                current_field += field_increment as isize;
+                let field_id = current_field as usize;
+                let field = fields.get(field_id).ok_or(Corruption::InvalidField(field_id))?;
+                de.skip_value(&field.type_def, interface_types)?;
+            }
+        })
+    }
+
+    fn skip_elements(&mut self, elem_def: &TypeDef, interface_types: &mut Vec<TypeId>) -> Result<()> {
+        self.nested(|de| {
+            let len = de.read_len()?;
+
+            for _ in 0..len {
+                de.skip_value(elem_def, interface_types)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn skip_entries(&mut self, key_def: &TypeDef, elem_def: &TypeDef, interface_types: &mut Vec<TypeId>) -> Result<()> {
+        self.nested(|de| {
+            let len = de.read_len()?;
+
+            for _ in 0..len {
+                de.skip_value(key_def, interface_types)?;
+                de.skip_value(elem_def, interface_types)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// An interface value is the name of its concrete type,
+    /// followed by the type's id and the length prefixed value, unless it's nil.
+    fn skip_interface(&mut self, interface_types: &mut Vec<TypeId>) -> Result<()> {
+        let name_len = self.reader.read_gob_usize()?;
+        if name_len == 0 {
+            return Ok(());
+        }
+
+        self.skip_bytes(name_len)?;
+
+        let type_id = self.reader.read_gob_type_id()?;
+        if type_id < 0 {
+            bail!(ErrorKind::Unsupported("type definitions inside interface values".into()))
+        }
+
+        self.look_up_type(type_id)?;
+        interface_types.push(type_id);
+
+        let len = self.reader.read_gob_usize()?;
+        self.skip_bytes(len)
+    }
+
+    /// Reads the length of a slice, array or map, enforcing the element limit.
+    /// Each element takes up at least one byte, so there can't be more than are left in the message.
+    pub(super) fn read_len(&mut self) -> Result<usize> {
+        let max = self.options().max_elements;
+        self.read_len_within(max, Limit::Elements)
+    }
+
+    /// Reads the number of fields of a struct type being defined, enforcing the field limit.
+    pub(super) fn read_field_count(&mut self) -> Result<usize> {
+        let max = self.options().max_fields;
+        self.read_len_within(max, Limit::Fields)
+    }
+
+    fn read_len_within(&mut self, max: usize, limit: fn(usize) -> Limit) -> Result<usize> {
+        let len = self.reader.read_gob_usize()?;
+
+        if len > max {
+            bail!(limit(len));
+        }
+
+        if len as u64 > self.remaining() {
+            bail!(Corruption::LengthPastMessageEnd(len));
+        }
+
+        Ok(len)
+    }
+
+    fn skip_bytes(&mut self, len: usize) -> Result<()> {
+        self.check_bytes_len(len)?;
+        self.reader.skip_gob_exact(len)
+    }
+}
@@ -0,0 +1,94 @@
+use std::borrow::BorrowMut;
+use std::io::Read;
+use std::marker::PhantomData;
+use serde::de::{Deserialize,DeserializeOwned};
+use errors::*;
+use super::{Deserializer,Input,SliceInput};
+
+/// Iterator over the values of a gob stream.
+///
+/// Created by [`Deserializer::into_iter`](struct.Deserializer.html#method.into_iter)
+/// and [`Deserializer::iter`](struct.Deserializer.html#method.iter).
+/// Type definitions are carried over from one item to the next.
+/// Iteration ends cleanly when the stream ends at a message boundary
+/// and stops after the first error.
+pub struct StreamDeserializer<R, T, D = Deserializer<R>> {
+    de: D,
+    item_offset: u64,
+    failed: bool,
+    marker: PhantomData<(R, T)>,
+}
+
+impl<'de, R, T, D> StreamDeserializer<R, T, D>
+    where R: Input<'de>, D: BorrowMut<Deserializer<R>>
+{
+    pub(super) fn new(de: D) -> Self {
+        let item_offset = de.borrow().offset();
+
+        StreamDeserializer {
+            de,
+            item_offset,
+            failed: false,
+            marker: PhantomData,
+        }
+    }
+
+    /// Byte offset at which the most recently returned item starts,
+    /// including any type definitions preceding its value.
+    pub fn item_offset(&self) -> u64 {
+        self.item_offset
+    }
+
+    /// Number of bytes consumed so far
+    pub fn byte_offset(&self) -> u64 {
+        self.de.borrow().offset()
+    }
+}
+
+impl<'de, R, T, D> StreamDeserializer<R, T, D>
+    where R: Input<'de>, T: Deserialize<'de>, D: BorrowMut<Deserializer<R>>
+{
+    fn next_value(&mut self) -> Option<Result<T>> {
+        if self.failed {
+            return None;
+        }
+
+        let de = self.de.borrow_mut();
+
+        match de.read_pending() {
+            Ok(true) => {},
+            Ok(false) => return None,
+            Err(err) => {
+                self.failed = true;
+                return Some(Err(err));
+            }
+        }
+
+        self.item_offset = de.item_offset;
+        let value = de.deserialize_next();
+        self.failed = value.is_err();
+        Some(value)
+    }
+}
+
+/// Values decoded from an `io::Read` can't borrow from it, so they must be owned
+impl<R, T, D> Iterator for StreamDeserializer<R, T, D>
+    where R: Read, T: DeserializeOwned, D: BorrowMut<Deserializer<R>>
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        self.next_value()
+    }
+}
+
+/// Values decoded from memory may borrow strings and byte slices from it
+impl<'de, T, D> Iterator for StreamDeserializer<SliceInput<'de>, T, D>
+    where T: Deserialize<'de>, D: BorrowMut<Deserializer<SliceInput<'de>>>
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        self.next_value()
+    }
+}
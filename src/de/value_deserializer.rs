@@ -1,37 +1,38 @@
-use std::io::Read;
 use serde;
 use serde::de::Visitor;
-use types::{TypeDef,WireTypeEnum};
+use types::{TypeDef,Plan};
 use errors::*;
-use super::ReadGob;
+use raw;
+use value;
+use super::{ReadGob,Input};
 
 pub struct ValueDeserializer<'a, R: 'a> {
     de: &'a mut super::Deserializer<R>,
     type_def: TypeDef,
 }
 
-impl<'a, R: Read + 'a> ValueDeserializer<'a, R> {
+impl<'a, 'de, R: Input<'de> + 'a> ValueDeserializer<'a, R> {
     pub fn new(de: &'a mut super::Deserializer<R>, type_def: TypeDef) -> Self {
         ValueDeserializer { de, type_def }
     }
 }
 
-impl<'a, 'b, 'de, R: Read> serde::Deserializer<'de> for &'b mut ValueDeserializer<'a, R> {
+impl<'a, 'b, 'de, R: Input<'de>> serde::Deserializer<'de> for &'b mut ValueDeserializer<'a, R> {
     type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
         where V: Visitor<'de>
     {
         trace!("### DE VALUE TYPE: {:?}", self.type_def);
-        match self.type_def.clone() {
-            TypeDef::Interface
-            | TypeDef::ArrayType
-            | TypeDef::Complex
-            | TypeDef::SliceType
-            | TypeDef::MapType => bail!("Decoding for {:?} not implemented", self.type_def),
+        let result = match self.type_def {
+            TypeDef::Interface => bail!(ErrorKind::Unsupported("interface values".into())),
+            TypeDef::Complex => bail!(ErrorKind::Unsupported("complex numbers".into())),
+            TypeDef::ArrayType => self.de.deserialize_map(visitor, TypeDef::ArrayType),
+            TypeDef::SliceType => self.de.deserialize_map(visitor, TypeDef::SliceType),
             TypeDef::StructType => self.de.deserialize_map(visitor, TypeDef::StructType),
-            TypeDef::ByteSlice
-            | TypeDef::String => visitor.visit_bytes(&self.de.reader().read_gob_bytes()?),
+            TypeDef::MapType => self.de.deserialize_map(visitor, TypeDef::MapType),
+            TypeDef::ByteSlice => self.de.visit_bytes(visitor, false),
+            TypeDef::String => self.de.visit_bytes(visitor, true),
             TypeDef::Bool => visitor.visit_bool(self.de.reader().read_gob_bool()?),
             TypeDef::Uint => visitor.visit_u64(self.de.reader().read_gob_u64()?),
             TypeDef::Float => visitor.visit_f64(self.de.reader().read_gob_f64()?),
@@ -40,14 +41,39 @@ impl<'a, 'b, 'de, R: Read> serde::Deserializer<'de> for &'b mut ValueDeserialize
             TypeDef::WireType => self.de.deserialize_map(visitor, TypeDef::WireType),
             TypeDef::FieldType => self.de.deserialize_map(visitor, TypeDef::FieldType),
             TypeDef::FieldTypeSlice => self.de.deserialize_seq(visitor, TypeDef::FieldType),
-            TypeDef::Custom(wire_type) => match *wire_type {
-                WireTypeEnum::Struct(_) => {
-                    let type_def = self.type_def.clone();
-                    self.de.deserialize_map(visitor, type_def)
+            TypeDef::Custom(ref wire_type) => {
+                let plan = self.de.plan(wire_type.id())?;
+                match *plan {
+                    Plan::Struct(_) => {
+                        let type_def = self.type_def.clone();
+                        self.de.deserialize_struct(visitor, type_def, plan.clone())
+                    }
+                    Plan::Elements(ref elem_def) => self.de.deserialize_seq(visitor, elem_def.clone()),
+                    Plan::Entries(ref key_def, ref elem_def) => self.de.deserialize_entries(visitor, key_def.clone(), elem_def.clone()),
                 }
-                _ => bail!("Decoding for {} not implemented")
             }
+        };
+
+        result.map_err(|err| err.in_type(self.type_def.name()))
+    }
+
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+        where V: Visitor<'de>
+    {
+        // A raw value is decoded later from a self-contained stream holding it
+        if name == raw::TOKEN {
+            let stream = self.de.capture_value(&self.type_def)
+                .map_err(|err| err.in_type(self.type_def.name()))?;
+            return visitor.visit_byte_buf(stream);
+        }
+
+        if name == value::TOKEN {
+            let value = self.de.read_dynamic(&self.type_def)
+                .map_err(|err| err.in_type(self.type_def.name()))?;
+            return value::visit(value, visitor);
         }
+
+        visitor.visit_newtype_struct(self)
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
@@ -59,7 +85,7 @@ impl<'a, 'b, 'de, R: Read> serde::Deserializer<'de> for &'b mut ValueDeserialize
 
     forward_to_deserialize_any! {
         bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
-        byte_buf unit unit_struct newtype_struct seq tuple
+        byte_buf unit unit_struct seq tuple
         tuple_struct map struct enum identifier ignored_any
     }
 }
@@ -1,26 +1,325 @@
 use std::io;
 use std::fmt;
+use std::error;
+use std::result;
+use serde::de::{Expected,Unexpected};
 use types::TypeId;
 
-error_chain! {
-    foreign_links {
-        Io(io::Error);
+pub type Result<T> = result::Result<T, Error>;
+
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+    context: Option<Box<Context>>,
+}
+
+/// The category of an error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The stream uses a gob feature that can't be decoded yet
+    Unsupported(String),
+    /// The stream is malformed
+    Corrupt(Corruption),
+    /// The data doesn't fit the type it is decoded into
+    TypeMismatch {
+        found: String,
+        expected: String,
+    },
+    /// A limit set by `DeserializerOptions` was exceeded
+    LimitExceeded(Limit),
+    /// A type definition was vetoed by the hook set with `Deserializer::on_define`
+    TypeRejected {
+        type_id: TypeId,
+        type_name: String,
+    },
+    /// A field path of a `Projection` is malformed or doesn't fit the type of the value
+    InvalidProjection(String),
+    /// Reading from the underlying reader failed
+    Io(io::Error),
+    /// An error raised by a `Deserialize` implementation
+    Custom(String),
+}
+
+/// Details of a malformed stream.
+#[derive(Debug,Clone,PartialEq)]
+#[non_exhaustive]
+pub enum Corruption {
+    NumZeroBytes,
+    NumOutOfRange,
+    InvalidField(usize),
+    AmbiguousWireType,
+    UndefinedType(TypeId),
+    TypeAlreadyDefined(TypeId),
+    DefiningIdMismatch(TypeId, TypeId),
+    DefiningBuiltin(TypeId),
+    DefiningReserved {
+        type_name: String,
+        type_id: TypeId,
+    },
+    NonZeroSingletonDelta,
+    /// An interface value doesn't take up as many bytes as its length prefix says
+    InterfaceLength(usize),
+    /// A length inside a message reaches past the end of the message
+    LengthPastMessageEnd(usize),
+    /// The content of a message doesn't end where the message's length prefix says
+    MessageLength {
+        expected_end: u64,
+        end: u64,
+    },
+    /// An array doesn't hold as many elements as its type specifies
+    ArrayLength {
+        type_name: String,
+        expected: usize,
+        len: usize,
+    },
+    DanglingTypeReference {
+        type_name: String,
+        field: String,
+        type_id: TypeId,
+    },
+}
+
+/// The limit that was exceeded, along with the offending value or the limit itself.
+#[derive(Debug,Clone,PartialEq)]
+#[non_exhaustive]
+pub enum Limit {
+    MessageSize(usize),
+    Depth(usize),
+    Elements(usize),
+    Types(usize),
+    Fields(usize),
+}
+
+/// Describes where in the stream an error occurred
+#[derive(Debug,Clone,Default)]
+pub struct Context {
+    offset: u64,
+    message: u64,
+    root: Option<String>,
+    path: Vec<PathSegment>,
+    type_name: Option<String>,
+}
+
+#[derive(Debug,Clone)]
+enum PathSegment {
+    Field(String),
+    Index(usize),
+}
+
+impl Error {
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    pub fn context(&self) -> Option<&Context> {
+        self.context.as_deref()
+    }
+
+    fn context_mut(&mut self) -> &mut Context {
+        self.context.get_or_insert_with(Default::default)
+    }
+
+    /// Records that the error occurred within the given struct field.
+    /// Fields are prepended while the error propagates outwards.
+    pub(crate) fn in_field(mut self, name: &str) -> Self {
+        self.context_mut().path.insert(0, PathSegment::Field(name.into()));
+        self
+    }
+
+    /// Records that the error occurred within the given element of a slice, array or map.
+    pub(crate) fn in_element(mut self, index: usize) -> Self {
+        self.context_mut().path.insert(0, PathSegment::Index(index));
+        self
+    }
+
+    /// Records the type being decoded, unless a more specific one is known already.
+    pub(crate) fn in_type(mut self, type_name: &str) -> Self {
+        let context = self.context_mut();
+        if context.type_name.is_none() {
+            context.type_name = Some(type_name.into());
+        }
+        self
+    }
+
+    /// Records the position in the stream and the type of the value being decoded.
+    pub(crate) fn at(mut self, offset: u64, message: u64, root: Option<&str>) -> Self {
+        let context = self.context_mut();
+        context.offset = offset;
+        context.message = message;
+        context.root = root.map(Into::into);
+        self
+    }
+}
+
+impl Context {
+    /// Byte offset in the stream at which the error was detected
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Index of the message in which the error occurred,
+    /// counting type definitions as well as values.
+    pub fn message_index(&self) -> u64 {
+        self.message
     }
 
-    errors {
-        NumZeroBytes {}
-        NumOutOfRange {}
-        InvalidField {}
-        AmbiguousWireType {}
-        UndefinedType(type_id: TypeId) {}
-        TypeAlreadyDefined(type_id: TypeId) {}
-        DefiningIdMismatch(type_id: TypeId, type_def_id: TypeId) {}
-        DefiningBuiltin(type_id: TypeId) {}
+    /// Go type name of the innermost value being decoded
+    pub fn type_name(&self) -> Option<&str> {
+        self.type_name.as_deref()
+    }
+
+    /// Path to the value being decoded, e.g. `Order.Items[3].Price`
+    pub fn path(&self) -> String {
+        let mut path = self.root.clone().unwrap_or_default();
+
+        for segment in &self.path {
+            match *segment {
+                PathSegment::Field(ref name) => {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str(name);
+                }
+                PathSegment::Index(index) => path.push_str(&format!("[{}]", index)),
+            }
+        }
+
+        path
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+
+        if let Some(ref context) = self.context {
+            let path = context.path();
+
+            if !path.is_empty() || context.type_name.is_some() {
+                write!(f, " at")?;
+
+                if !path.is_empty() {
+                    write!(f, " {}", path)?;
+                }
+
+                if let Some(ref type_name) = context.type_name {
+                    write!(f, " ({})", type_name)?;
+                }
+            }
+
+            write!(f, " in message {}, byte offset {}", context.message, context.offset)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ErrorKind::Unsupported(ref feature) => write!(f, "unsupported: {}", feature),
+            ErrorKind::Corrupt(ref corruption) => write!(f, "corrupt data: {}", corruption),
+            ErrorKind::TypeMismatch { ref found, ref expected } =>
+                write!(f, "type mismatch: found {}, expected {}", found, expected),
+            ErrorKind::LimitExceeded(ref limit) => write!(f, "limit exceeded: {}", limit),
+            ErrorKind::TypeRejected { type_id, ref type_name } =>
+                write!(f, "definition of type '{}' ({}) was rejected", type_name, type_id),
+            ErrorKind::InvalidProjection(ref msg) => write!(f, "invalid projection: {}", msg),
+            ErrorKind::Io(ref err) => write!(f, "{}", err),
+            ErrorKind::Custom(ref msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl fmt::Display for Corruption {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Corruption::NumZeroBytes => write!(f, "number encoded with zero bytes"),
+            Corruption::NumOutOfRange => write!(f, "number out of range"),
+            Corruption::InvalidField(field) => write!(f, "invalid field number {}", field),
+            Corruption::AmbiguousWireType => write!(f, "ambiguous wire type"),
+            Corruption::UndefinedType(type_id) => write!(f, "undefined type {}", type_id),
+            Corruption::TypeAlreadyDefined(type_id) => write!(f, "type {} is already defined", type_id),
+            Corruption::DefiningIdMismatch(type_id, type_def_id) =>
+                write!(f, "type {} is defined with mismatching id {}", type_id, type_def_id),
+            Corruption::DefiningBuiltin(type_id) => write!(f, "builtin type {} can't be redefined", type_id),
+            Corruption::DefiningReserved { ref type_name, type_id } =>
+                write!(f, "type '{}' is defined with reserved id {}", type_name, type_id),
+            Corruption::NonZeroSingletonDelta => write!(f, "non-zero field delta for singleton value"),
+            Corruption::InterfaceLength(len) => write!(f, "interface value doesn't match its length of {} bytes", len),
+            Corruption::LengthPastMessageEnd(len) => write!(f, "length {} reaches past the end of the message", len),
+            Corruption::MessageLength { expected_end, end } =>
+                write!(f, "message content ends at byte {} instead of byte {}", end, expected_end),
+            Corruption::ArrayLength { ref type_name, expected, len } =>
+                write!(f, "array of type '{}' holds {} elements instead of {}", type_name, len, expected),
+            Corruption::DanglingTypeReference { ref type_name, ref field, type_id } =>
+                write!(f, "field '{}' of type '{}' refers to undefined type {}", field, type_name, type_id),
+        }
+    }
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Limit::MessageSize(size) => write!(f, "message or value of {} bytes is too large", size),
+            Limit::Depth(limit) => write!(f, "nesting depth exceeds {}", limit),
+            Limit::Elements(len) => write!(f, "{} elements are too many", len),
+            Limit::Types(limit) => write!(f, "more than {} types defined", limit),
+            Limit::Fields(count) => write!(f, "{} fields of a struct type are too many", count),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self.kind {
+            ErrorKind::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Error { kind, context: None }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        ErrorKind::Io(err).into()
+    }
+}
+
+impl From<Corruption> for Error {
+    fn from(corruption: Corruption) -> Self {
+        ErrorKind::Corrupt(corruption).into()
+    }
+}
+
+impl From<Limit> for Error {
+    fn from(limit: Limit) -> Self {
+        ErrorKind::LimitExceeded(limit).into()
     }
 }
 
 impl ::serde::de::Error for Error {
     fn custom<T: fmt::Display>(msg: T) -> Self {
-        ErrorKind::Msg(msg.to_string()).into()
+        ErrorKind::Custom(msg.to_string()).into()
+    }
+
+    fn invalid_type(unexpected: Unexpected, expected: &dyn Expected) -> Self {
+        ErrorKind::TypeMismatch {
+            found: unexpected.to_string(),
+            expected: expected.to_string(),
+        }.into()
+    }
+
+    fn invalid_length(len: usize, expected: &dyn Expected) -> Self {
+        ErrorKind::TypeMismatch {
+            found: format!("length {}", len),
+            expected: expected.to_string(),
+        }.into()
     }
 }
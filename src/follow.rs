@@ -0,0 +1,234 @@
+use std::fs::{File,Metadata};
+use std::io;
+use std::marker::PhantomData;
+use std::path::{Path,PathBuf};
+use std::thread;
+use std::time::Duration;
+use serde::de::DeserializeOwned;
+use de::{Deserializer,DeserializerOptions};
+use errors::*;
+
+/// Number of bytes in front of the current offset compared to notice a rewritten file
+const TAIL_LEN: usize = 64;
+
+/// Decodes the values of a gob file while another process keeps appending to it, like `tail -F`.
+///
+/// A value whose messages haven't been written completely yet is left alone
+/// until the rest of it arrives, so values are neither lost nor decoded twice.
+/// If the file is truncated, decoding starts over from its beginning.
+/// If it is replaced, e.g. by log rotation, the rest of the old file is decoded
+/// before moving on to the new one.
+/// In both cases, the new contents must start with their own type definitions.
+///
+/// Truncation is noticed by the file becoming shorter than the part decoded so far.
+/// On unix, a file that has been truncated and written past that point again
+/// is noticed as well, by a change in the last bytes decoded,
+/// unless it was rewritten with the very same bytes in front of the current offset.
+///
+/// Iterating blocks until the next value is available.
+/// Iteration stops after the first error.
+///
+/// ```no_run
+/// # #[macro_use] extern crate serde_derive;
+/// # extern crate gob;
+/// # use std::time::Duration;
+/// #[derive(Deserialize)]
+/// struct Event {
+///     Id: u64,
+/// }
+///
+/// # fn main() -> gob::Result<()> {
+/// let follower = gob::Follower::<Event>::open("events.gob")?
+///     .poll_interval(Duration::from_millis(500));
+///
+/// for event in follower {
+///     println!("event {}", event?.Id);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct Follower<T> {
+    path: PathBuf,
+    file: File,
+    metadata: Metadata,
+    de: Deserializer<File>,
+    /// The last bytes decoded, up to `TAIL_LEN`
+    tail: Vec<u8>,
+    poll_interval: Duration,
+    failed: bool,
+    marker: PhantomData<T>,
+}
+
+impl<T: DeserializeOwned> Follower<T> {
+    /// Starts following the file at `path` from its beginning
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::with_options(path, DeserializerOptions::default())
+    }
+
+    pub fn with_options<P: AsRef<Path>>(path: P, options: DeserializerOptions) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::open(&path)?;
+        let metadata = file.metadata()?;
+        let de = Deserializer::with_options(file.try_clone()?, options);
+
+        Ok(Follower {
+            path,
+            file,
+            metadata,
+            de,
+            tail: Vec::new(),
+            poll_interval: Duration::from_millis(100),
+            failed: false,
+            marker: PhantomData,
+        })
+    }
+
+    /// How long iteration waits before checking the file for new data again.
+    /// Defaults to 100ms.
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Offset of the next value in the file currently followed
+    pub fn offset(&self) -> u64 {
+        self.de.offset()
+    }
+
+    /// Decodes the next value if it has been written completely.
+    /// Returns `None` without waiting otherwise.
+    ///
+    /// After an error, the follower stays in front of the offending value.
+    pub fn try_next(&mut self) -> Result<Option<T>> {
+        if !self.rewritten()? {
+            if let Some(value) = self.decode()? {
+                return Ok(Some(value));
+            }
+        }
+
+        let (file, metadata) = match self.replacement()? {
+            Some(replacement) => replacement,
+            None => return Ok(None),
+        };
+
+        // The writer may have appended to the old file right before replacing it
+        if !is_same_file(&self.metadata, &metadata) {
+            if let Some(value) = self.decode()? {
+                return Ok(Some(value));
+            }
+        }
+
+        debug!("Following {} from its beginning", self.path.display());
+
+        let options = self.de.options().clone();
+        self.de = Deserializer::with_options(file.try_clone()?, options);
+        self.file = file;
+        self.metadata = metadata;
+        self.tail.clear();
+
+        self.decode()
+    }
+
+    /// Decodes the next value, going back to where it starts if that fails
+    fn decode(&mut self) -> Result<Option<T>> {
+        let checkpoint = self.de.checkpoint();
+
+        self.de.reader.start_recording();
+        let result = self.de.deserialize();
+        let decoded = self.de.reader.stop_recording();
+
+        let err = match result {
+            Ok(value) => {
+                self.tail.extend_from_slice(&decoded);
+                let excess = self.tail.len().saturating_sub(TAIL_LEN);
+                self.tail.drain(..excess);
+                return Ok(Some(value));
+            }
+            Err(err) => err,
+        };
+
+        // The deserializer is unusable after an error, so start over in front of the value
+        let options = self.de.options().clone();
+        self.de = Deserializer::resume_with_options(self.file.try_clone()?, checkpoint, options)?;
+
+        match *err.kind() {
+            ErrorKind::Io(ref io_err) if io_err.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+            _ => Err(err),
+        }
+    }
+
+    /// Checks whether the file at `path` has been truncated or replaced by another file,
+    /// and returns the file to continue with if so
+    fn replacement(&self) -> Result<Option<(File, Metadata)>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            // The file is being rotated and its successor hasn't been created yet
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+
+        let metadata = file.metadata()?;
+
+        if !is_same_file(&self.metadata, &metadata) || metadata.len() < self.de.offset() || self.rewritten()? {
+            Ok(Some((file, metadata)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Checks whether the last bytes decoded have changed,
+    /// which means that the file has been truncated and written past the current offset again.
+    /// The bytes are read without moving the file position the deserializer relies on.
+    #[cfg(unix)]
+    fn rewritten(&self) -> Result<bool> {
+        use std::os::unix::fs::FileExt;
+
+        let mut bytes = vec![0; self.tail.len()];
+        let start = self.de.offset() - bytes.len() as u64;
+
+        match self.file.read_exact_at(&mut bytes, start) {
+            Ok(()) => Ok(bytes != self.tail),
+            Err(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(true),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Without positional reads, only truncation below the current offset can be detected
+    #[cfg(not(unix))]
+    fn rewritten(&self) -> Result<bool> {
+        Ok(false)
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for Follower<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        if self.failed {
+            return None;
+        }
+
+        loop {
+            match self.try_next() {
+                Ok(Some(value)) => return Some(Ok(value)),
+                Ok(None) => thread::sleep(self.poll_interval),
+                Err(err) => {
+                    self.failed = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn is_same_file(a: &Metadata, b: &Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    a.dev() == b.dev() && a.ino() == b.ino()
+}
+
+/// Without file identities, only truncation can be detected
+#[cfg(not(unix))]
+fn is_same_file(_: &Metadata, _: &Metadata) -> bool {
+    true
+}
@@ -1,14 +1,33 @@
 extern crate byteorder;
 #[macro_use] extern crate serde;
 #[macro_use] extern crate serde_derive;
-#[macro_use] extern crate error_chain;
 #[macro_use] extern crate log;
+#[cfg(feature = "memmap")] extern crate memmap2;
+#[cfg(feature = "tokio")] extern crate tokio;
+#[cfg(feature = "codec")] extern crate tokio_util;
+#[cfg(feature = "codec")] extern crate bytes;
 
+#[macro_use] mod macros;
 pub mod errors;
 pub mod de;
+pub mod schema;
+#[cfg(feature = "codec")]
+pub mod codec;
+mod follow;
+mod projection;
+mod raw;
+mod router;
 mod types;
+pub mod value;
+mod write_gob;
 
-pub use de::Deserializer;
+pub use de::{Checkpoint,Deserializer,DeserializerOptions,Index,IndexEntry,Message,ParallelDeserializer,PushDecoder,StreamDeserializer};
+#[cfg(feature = "tokio")]
+pub use de::AsyncDeserializer;
+pub use follow::Follower;
+pub use projection::Projection;
+pub use raw::RawValue;
+pub use router::Router;
 pub use errors::*;
-
-type TypeMap = ::std::collections::HashMap<types::TypeId, types::TypeDef>;
+pub use types::{TypeId,TypeMap};
+pub use value::{Value,Interface};
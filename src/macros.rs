@@ -0,0 +1,6 @@
+/// Returns early with the given error, converted into `Error`
+macro_rules! bail {
+    ($e:expr) => {
+        return Err($e.into())
+    };
+}
@@ -0,0 +1,177 @@
+//! Decoding selected parts of values only.
+
+use std::collections::{BTreeMap,HashMap};
+use std::sync::{Arc,Mutex};
+use de::ProjectionPlan;
+use errors::*;
+use types::{TypeId,WireTypeEnum};
+
+/// A set of field paths to decode, skipping everything else on the wire.
+///
+/// Paths name struct fields separated by dots, with `[]` standing for
+/// every element of a slice or array, or every value of a map,
+/// e.g. `Header.Timestamp` or `Items[].Sku`.
+/// A path starting with `[]` refers to the elements of a top-level slice.
+///
+/// The value is presented to the `Deserialize` implementation as if the
+/// producer had sent the selected fields only, so the target type
+/// usually mirrors the paths:
+///
+/// ```no_run
+/// # #[macro_use] extern crate serde_derive;
+/// # extern crate gob;
+/// # use std::fs::File;
+/// #[derive(Deserialize)]
+/// struct Order {
+///     Header: Header,
+///     Items: Vec<Item>,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Header {
+///     Timestamp: i64,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Item {
+///     Sku: String,
+/// }
+///
+/// # fn main() -> gob::Result<()> {
+/// let projection = gob::Projection::new(&["Header.Timestamp", "Items[].Sku"])?;
+/// let mut de = gob::Deserializer::new(File::open("orders.gob")?);
+///
+/// while de.peek_type()?.is_some() {
+///     let order: Order = de.deserialize_projected(&projection)?;
+///     println!("{} {}", order.Header.Timestamp, order.Items.len());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The projection is resolved against the type of the first value of each type
+/// it's applied to, and the result is kept for the following values.
+/// Clones share these, so a projection is best created once and reused.
+#[derive(Debug,Clone)]
+pub struct Projection {
+    selection: Selection,
+    plans: Arc<Mutex<Plans>>,
+}
+
+/// The plans compiled so far by root type, along with the definitions they were compiled for,
+/// since type ids are only unique within a stream
+type Plans = HashMap<TypeId, (Arc<WireTypeEnum>, Arc<ProjectionPlan>)>;
+
+/// The part of a value selected by a projection
+#[derive(Debug,Clone,Default)]
+pub(crate) struct Selection {
+    /// The whole value is selected
+    pub(crate) all: bool,
+    pub(crate) fields: BTreeMap<String, Selection>,
+    pub(crate) elements: Option<Box<Selection>>,
+}
+
+enum Segment<'a> {
+    Field(&'a str),
+    Elements,
+}
+
+impl Projection {
+    /// Parses the given field paths
+    pub fn new<I, S>(paths: I) -> Result<Self>
+        where I: IntoIterator<Item = S>, S: AsRef<str>
+    {
+        let mut selection = Selection::default();
+        let mut empty = true;
+
+        for path in paths {
+            let segments = parse(path.as_ref())?;
+            selection.insert(&segments);
+            empty = false;
+        }
+
+        if empty {
+            bail!(ErrorKind::InvalidProjection("no field paths given".into()))
+        }
+
+        Ok(Projection { selection, plans: Default::default() })
+    }
+
+    pub(crate) fn selection(&self) -> &Selection {
+        &self.selection
+    }
+
+    /// The plan compiled before for values of the given type, if it has the same definition
+    pub(crate) fn cached_plan(&self, wire_type: &Arc<WireTypeEnum>) -> Option<Arc<ProjectionPlan>> {
+        let plans = self.plans.lock().expect("projection plans poisoned");
+
+        match plans.get(&wire_type.id()) {
+            Some((cached, plan)) if Arc::ptr_eq(cached, wire_type) => Some(plan.clone()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn cache_plan(&self, wire_type: Arc<WireTypeEnum>, plan: Arc<ProjectionPlan>) {
+        let mut plans = self.plans.lock().expect("projection plans poisoned");
+        plans.insert(wire_type.id(), (wire_type, plan));
+    }
+}
+
+impl Selection {
+    fn insert(&mut self, segments: &[Segment]) {
+        if self.all {
+            return;
+        }
+
+        let (segment, rest) = match segments.split_first() {
+            Some(split) => split,
+            None => {
+                // Selecting a value in full supersedes selecting parts of it
+                *self = Selection { all: true, ..Selection::default() };
+                return;
+            }
+        };
+
+        let child = match *segment {
+            Segment::Field(name) => self.fields.entry(name.into()).or_default(),
+            Segment::Elements => &mut **self.elements.get_or_insert_with(Default::default),
+        };
+
+        child.insert(rest);
+    }
+}
+
+fn parse(path: &str) -> Result<Vec<Segment<'_>>> {
+    let invalid = || ErrorKind::InvalidProjection(format!("malformed field path '{}'", path));
+    let mut segments = Vec::new();
+
+    for (i, part) in path.split('.').enumerate() {
+        let name_len = part.find("[]").unwrap_or(part.len());
+        let (name, mut elements) = part.split_at(name_len);
+
+        // Only the first part may consist of elements alone
+        if name.is_empty() && (i > 0 || elements.is_empty()) {
+            bail!(invalid())
+        }
+
+        if name.contains(['[', ']']) {
+            bail!(invalid())
+        }
+
+        if !name.is_empty() {
+            segments.push(Segment::Field(name));
+        }
+
+        while !elements.is_empty() {
+            if !elements.starts_with("[]") {
+                bail!(invalid())
+            }
+
+            segments.push(Segment::Elements);
+            elements = &elements[2..];
+        }
+    }
+
+    Ok(segments)
+}
+
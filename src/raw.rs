@@ -0,0 +1,96 @@
+//! Gob values kept undecoded for later.
+
+use std::fmt;
+use std::io;
+use serde::de::{self,Deserialize,DeserializeOwned,Visitor};
+use de::Deserializer;
+use errors::*;
+use schema::TypeSchema;
+
+/// Name of the newtype struct the deserializer recognizes as a request for raw bytes
+pub(crate) const TOKEN: &str = "$gob::private::RawValue";
+
+/// An undecoded gob value.
+///
+/// Deserializing into a `RawValue`, either at the top level or as a field,
+/// skips over the value and keeps its wire bytes.
+/// The definitions of all types the value needs are kept along with it,
+/// so it stays decodable after the original stream is gone.
+///
+/// ```no_run
+/// # #[macro_use] extern crate serde_derive;
+/// # extern crate gob;
+/// # use std::net::TcpStream;
+/// #[derive(Deserialize)]
+/// struct Envelope {
+///     Topic: String,
+///     Payload: gob::RawValue,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Order {
+///     Id: u64,
+/// }
+///
+/// # fn main() -> gob::Result<()> {
+/// # let stream = TcpStream::connect("localhost:1234")?;
+/// let envelope: Envelope = gob::Deserializer::new(stream).deserialize()?;
+/// if envelope.Topic == "orders" {
+///     let order: Order = envelope.Payload.decode()?;
+///     println!("order {}", order.Id);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug,Clone,PartialEq)]
+pub struct RawValue {
+    stream: Vec<u8>,
+}
+
+impl RawValue {
+    /// Decodes the value
+    pub fn decode<T: DeserializeOwned>(&self) -> Result<T> {
+        Deserializer::new(self.stream.as_slice()).deserialize()
+    }
+
+    /// Describes the type of the value
+    pub fn schema(&self) -> Result<TypeSchema> {
+        Deserializer::new(self.stream.as_slice()).peek_type()?
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof).into())
+    }
+
+    /// The value as a gob stream consisting of type definitions and a single value
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.stream
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.stream
+    }
+}
+
+impl<'de> Deserialize<'de> for RawValue {
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        struct RawValueVisitor;
+
+        impl<'de> Visitor<'de> for RawValueVisitor {
+            type Value = RawValue;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a raw gob value")
+            }
+
+            fn visit_bytes<E: de::Error>(self, stream: &[u8]) -> ::std::result::Result<RawValue, E> {
+                Ok(RawValue { stream: stream.into() })
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, stream: Vec<u8>) -> ::std::result::Result<RawValue, E> {
+                Ok(RawValue { stream })
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(TOKEN, RawValueVisitor)
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use serde::de::DeserializeOwned;
+use de::{Deserializer,Input};
+use errors::*;
+use raw::RawValue;
+use schema::TypeSchema;
+
+type Handler<'a, R> = Box<dyn FnMut(&mut Deserializer<R>) -> Result<()> + 'a>;
+type Fallback<'a> = Box<dyn FnMut(&TypeSchema, RawValue) + 'a>;
+
+/// Dispatches the values of a heterogeneous stream to handlers by their Go type name.
+///
+/// Type names are matched exactly as the producer sent them,
+/// which for Go structs is usually the unqualified name, e.g. `Heartbeat`.
+/// Values without a handler are passed to the fallback, or skipped if there is none.
+///
+/// ```no_run
+/// # #[macro_use] extern crate serde_derive;
+/// # extern crate gob;
+/// # use std::net::TcpStream;
+/// #[derive(Deserialize)]
+/// struct Heartbeat {
+///     Seq: u64,
+/// }
+///
+/// # fn main() -> gob::Result<()> {
+/// # let stream = TcpStream::connect("localhost:1234")?;
+/// let mut de = gob::Deserializer::new(stream);
+/// gob::Router::new()
+///     .on("Heartbeat", |hb: Heartbeat| println!("heartbeat {}", hb.Seq))
+///     .fallback(|schema, _| println!("ignoring {}", schema.name()))
+///     .run(&mut de)
+/// # }
+/// ```
+pub struct Router<'a, R> {
+    handlers: HashMap<String, Handler<'a, R>>,
+    fallback: Option<Fallback<'a>>,
+}
+
+impl<'a, 'de, R: Input<'de>> Router<'a, R> {
+    pub fn new() -> Self {
+        Router {
+            handlers: HashMap::new(),
+            fallback: None,
+        }
+    }
+
+    /// Registers a handler for values of the given Go type
+    pub fn on<T, F>(&mut self, type_name: &str, mut handler: F) -> &mut Self
+        where T: DeserializeOwned, F: FnMut(T) + 'a
+    {
+        let handler = move |de: &mut Deserializer<R>| {
+            handler(de.deserialize_next()?);
+            Ok(())
+        };
+
+        self.handlers.insert(type_name.into(), Box::new(handler));
+        self
+    }
+
+    /// Registers a handler for values of all other types.
+    /// It receives the value's type and the undecoded value.
+    pub fn fallback<F>(&mut self, handler: F) -> &mut Self
+        where F: FnMut(&TypeSchema, RawValue) + 'a
+    {
+        self.fallback = Some(Box::new(handler));
+        self
+    }
+
+    /// Dispatches the next value of the stream.
+    /// Returns false if the stream has ended.
+    pub fn dispatch(&mut self, de: &mut Deserializer<R>) -> Result<bool> {
+        if !de.read_pending()? {
+            return Ok(false);
+        }
+
+        let handler = de.pending_type()
+            .and_then(|type_def| self.handlers.get_mut(type_def.name()));
+
+        if let Some(handler) = handler {
+            handler(de)?;
+            return Ok(true);
+        }
+
+        match self.fallback {
+            Some(ref mut fallback) => {
+                let schema = de.pending_type().map(TypeSchema::new);
+                let value = de.deserialize_next()?;
+
+                if let Some(schema) = schema {
+                    fallback(&schema, value);
+                }
+            }
+            None => {
+                de.skip_pending()?;
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Dispatches all values until the end of the stream
+    pub fn run(&mut self, de: &mut Deserializer<R>) -> Result<()> {
+        while self.dispatch(de)? {}
+        Ok(())
+    }
+}
+
+impl<'a, 'de, R: Input<'de>> Default for Router<'a, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,170 @@
+//! Read-only descriptions of the types sent by a gob producer.
+//!
+//! The types a stream has defined can be listed with [`Deserializer::types`],
+//! and looked up by id with [`TypeMap::schema`].
+//!
+//! ```no_run
+//! # extern crate gob;
+//! # use std::fs::File;
+//! # use gob::schema::Kind;
+//! # fn main() -> gob::Result<()> {
+//! let mut de = gob::Deserializer::new(File::open("scores.gob")?);
+//! // Reads the definitions in front of the first value
+//! de.peek_type()?;
+//!
+//! for schema in de.types() {
+//!     if schema.kind() == Kind::Struct {
+//!         for field in schema.fields() {
+//!             println!("{}.{} has type {}", schema.name(), field.name(), field.type_id());
+//!         }
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Deserializer::types`]: ../struct.Deserializer.html#method.types
+//! [`TypeMap::schema`]: ../struct.TypeMap.html#method.schema
+
+use types::{TypeId,TypeDef,WireTypeEnum};
+
+/// Describes a type as defined in a gob stream
+#[derive(Debug,Clone,PartialEq)]
+pub struct TypeSchema {
+    id: TypeId,
+    name: String,
+    kind: Kind,
+    fields: Vec<FieldSchema>,
+    elem: Option<TypeId>,
+    key: Option<TypeId>,
+    len: Option<usize>,
+}
+
+/// Describes a field of a struct type
+#[derive(Debug,Clone,PartialEq)]
+pub struct FieldSchema {
+    name: String,
+    type_id: TypeId,
+}
+
+/// How values of a type are encoded, similar to Go's `reflect.Kind`
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+#[non_exhaustive]
+pub enum Kind {
+    Bool,
+    /// Any signed integer
+    Int,
+    /// Any unsigned integer
+    Uint,
+    /// `float32` or `float64`
+    Float,
+    /// `[]byte`, which is encoded unlike other slices
+    Bytes,
+    String,
+    /// `complex64` or `complex128`
+    Complex,
+    /// An interface value, holding a value of a named type
+    Interface,
+    Struct,
+    Slice,
+    Array,
+    Map,
+}
+
+impl TypeSchema {
+    pub(crate) fn new(type_def: &TypeDef) -> Self {
+        let mut schema = TypeSchema {
+            id: type_def.id(),
+            name: type_def.name().into(),
+            kind: Kind::Struct,
+            fields: Vec::new(),
+            elem: None,
+            key: None,
+            len: None,
+        };
+
+        schema.kind = match *type_def {
+            TypeDef::Bool => Kind::Bool,
+            TypeDef::Int => Kind::Int,
+            TypeDef::Uint => Kind::Uint,
+            TypeDef::Float => Kind::Float,
+            TypeDef::ByteSlice => Kind::Bytes,
+            TypeDef::String => Kind::String,
+            TypeDef::Complex => Kind::Complex,
+            TypeDef::Interface => Kind::Interface,
+            TypeDef::Custom(ref wire_type) => match **wire_type {
+                WireTypeEnum::Struct(ref t) => {
+                    schema.fields = t.fields().iter()
+                        .map(|field| FieldSchema {
+                            name: field.name().into(),
+                            type_id: field.id(),
+                        })
+                        .collect();
+                    Kind::Struct
+                }
+                WireTypeEnum::Slice(ref t) => {
+                    schema.elem = Some(t.elem());
+                    Kind::Slice
+                }
+                WireTypeEnum::Array(ref t) => {
+                    schema.elem = Some(t.elem());
+                    schema.len = if t.len() >= 0 { Some(t.len() as usize) } else { None };
+                    Kind::Array
+                }
+                WireTypeEnum::Map(ref t) => {
+                    schema.key = Some(t.key());
+                    schema.elem = Some(t.elem());
+                    Kind::Map
+                }
+            },
+            // The types describing type definitions themselves
+            _ => Kind::Struct,
+        };
+
+        schema
+    }
+
+    pub fn id(&self) -> TypeId {
+        self.id
+    }
+
+    /// The name Go uses for the type, e.g. `main.Point` or `[]int`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    /// The fields of a struct type, in wire order. Empty for other types.
+    pub fn fields(&self) -> &[FieldSchema] {
+        &self.fields
+    }
+
+    /// The element type of a slice, array or map
+    pub fn elem(&self) -> Option<TypeId> {
+        self.elem
+    }
+
+    /// The key type of a map
+    pub fn key(&self) -> Option<TypeId> {
+        self.key
+    }
+
+    /// The length of an array
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> Option<usize> {
+        self.len
+    }
+}
+
+impl FieldSchema {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+}
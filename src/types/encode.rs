@@ -0,0 +1,152 @@
+use std::io::{self,Write};
+use write_gob::WriteGob;
+use super::{WireTypeEnum,ArrayType,CommonType,SliceType,StructType,FieldType,MapType};
+
+/// Writes the field delta from the last written field to `field`
+fn write_field<W: Write>(w: &mut W, last: &mut u64, field: u64) -> io::Result<()> {
+    w.write_gob_u64(field + 1 - *last)?;
+    *last = field + 1;
+    Ok(())
+}
+
+impl WireTypeEnum {
+    /// Encodes the type definition like Go's encoder does, omitting zero values
+    pub fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut last = 0;
+
+        match *self {
+            WireTypeEnum::Array(ref t) => {
+                write_field(w, &mut last, 0)?;
+                t.encode(w)?;
+            }
+            WireTypeEnum::Slice(ref t) => {
+                write_field(w, &mut last, 1)?;
+                t.encode(w)?;
+            }
+            WireTypeEnum::Struct(ref t) => {
+                write_field(w, &mut last, 2)?;
+                t.encode(w)?;
+            }
+            WireTypeEnum::Map(ref t) => {
+                write_field(w, &mut last, 3)?;
+                t.encode(w)?;
+            }
+        }
+
+        w.write_gob_u64(0)
+    }
+}
+
+impl CommonType {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut last = 0;
+
+        if !self.name.is_empty() {
+            write_field(w, &mut last, 0)?;
+            w.write_gob_bytes(self.name.as_bytes())?;
+        }
+
+        if self.id != 0 {
+            write_field(w, &mut last, 1)?;
+            w.write_gob_type_id(self.id)?;
+        }
+
+        w.write_gob_u64(0)
+    }
+}
+
+impl ArrayType {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut last = 0;
+
+        write_field(w, &mut last, 0)?;
+        self.common.encode(w)?;
+
+        if self.elem != 0 {
+            write_field(w, &mut last, 1)?;
+            w.write_gob_type_id(self.elem)?;
+        }
+
+        if self.len != 0 {
+            write_field(w, &mut last, 2)?;
+            w.write_gob_i64(self.len as i64)?;
+        }
+
+        w.write_gob_u64(0)
+    }
+}
+
+impl SliceType {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut last = 0;
+
+        write_field(w, &mut last, 0)?;
+        self.common.encode(w)?;
+
+        if self.elem != 0 {
+            write_field(w, &mut last, 1)?;
+            w.write_gob_type_id(self.elem)?;
+        }
+
+        w.write_gob_u64(0)
+    }
+}
+
+impl StructType {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut last = 0;
+
+        write_field(w, &mut last, 0)?;
+        self.common.encode(w)?;
+
+        if !self.fields.is_empty() {
+            write_field(w, &mut last, 1)?;
+            w.write_gob_usize(self.fields.len())?;
+
+            for field in &self.fields {
+                field.encode(w)?;
+            }
+        }
+
+        w.write_gob_u64(0)
+    }
+}
+
+impl FieldType {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut last = 0;
+
+        if !self.name.is_empty() {
+            write_field(w, &mut last, 0)?;
+            w.write_gob_bytes(self.name.as_bytes())?;
+        }
+
+        if self.id != 0 {
+            write_field(w, &mut last, 1)?;
+            w.write_gob_type_id(self.id)?;
+        }
+
+        w.write_gob_u64(0)
+    }
+}
+
+impl MapType {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut last = 0;
+
+        write_field(w, &mut last, 0)?;
+        self.common.encode(w)?;
+
+        if self.key != 0 {
+            write_field(w, &mut last, 1)?;
+            w.write_gob_type_id(self.key)?;
+        }
+
+        if self.elem != 0 {
+            write_field(w, &mut last, 2)?;
+            w.write_gob_type_id(self.elem)?;
+        }
+
+        w.write_gob_u64(0)
+    }
+}
@@ -1,8 +1,11 @@
 mod type_def;
 mod wire_type;
+mod encode;
+mod type_map;
 
 pub use self::type_def::TypeDef;
 pub use self::wire_type::{WireType, WireTypeEnum};
+pub use self::type_map::{TypeMap,Plan,FieldPlan};
 
 pub mod ids {
     use super::TypeId;
@@ -22,33 +25,54 @@ pub mod ids {
     pub const FIELD_TYPE_ID: TypeId = 21;
     pub const FIELD_TYPE_SLICE_ID: TypeId = 22;
     pub const MAP_TYPE_ID: TypeId = 23;
+
+    // Ids 24 through 63 are reserved by Go for future builtins
+    pub const FIRST_RESERVED_ID: TypeId = 24;
+    pub const FIRST_USER_ID: TypeId = 64;
 }
 
 pub type TypeId = i64;
 pub type FieldId = isize; // TODO: isize => usize
 
-#[derive(Default,Debug,Clone,Deserialize)]
+#[derive(Default,Debug,Clone,Serialize,Deserialize)]
+#[serde(default)]
 pub struct ArrayType {
-    #[serde(rename="CommenType")] common: CommonType,
+    #[serde(rename="CommonType")] common: CommonType,
           #[serde(rename="Elem")] elem: TypeId,
            #[serde(rename="Len")] len: isize,
 }
 
-#[derive(Default,Debug,Clone,Deserialize)]
+impl ArrayType {
+    pub(crate) fn elem(&self) -> TypeId {
+        self.elem
+    }
+
+    pub(crate) fn len(&self) -> isize {
+        self.len
+    }
+}
+
+#[derive(Default,Debug,Clone,Serialize,Deserialize)]
 #[serde(default)]
 pub struct CommonType {
     #[serde(rename="Name")] name: String,
       #[serde(rename="Id")] id: TypeId,
 }
 
-#[derive(Default,Debug,Clone,Deserialize)]
+#[derive(Default,Debug,Clone,Serialize,Deserialize)]
 #[serde(default)]
 pub struct SliceType {
     #[serde(rename="CommonType")] common: CommonType,
           #[serde(rename="Elem")] elem: TypeId,
 }
 
-#[derive(Default,Debug,Clone,Deserialize)]
+impl SliceType {
+    pub(crate) fn elem(&self) -> TypeId {
+        self.elem
+    }
+}
+
+#[derive(Default,Debug,Clone,Serialize,Deserialize)]
 #[serde(default)]
 pub struct StructType {
     #[serde(rename="CommonType")] common: CommonType,
@@ -61,7 +85,7 @@ impl StructType {
     }
 }
 
-#[derive(Default,Debug,Clone,Deserialize)]
+#[derive(Default,Debug,Clone,Serialize,Deserialize)]
 #[serde(default)]
 pub struct FieldType {
     #[serde(rename="Name")] name: String,
@@ -78,10 +102,20 @@ impl FieldType {
     }
 }
 
-#[derive(Default,Debug,Clone,Deserialize)]
+#[derive(Default,Debug,Clone,Serialize,Deserialize)]
 #[serde(default)]
 pub struct MapType {
-    common: CommonType,
-    key: TypeId,
-    elem: TypeId,
+    #[serde(rename="CommonType")] common: CommonType,
+           #[serde(rename="Key")] key: TypeId,
+          #[serde(rename="Elem")] elem: TypeId,
+}
+
+impl MapType {
+    pub(crate) fn key(&self) -> TypeId {
+        self.key
+    }
+
+    pub(crate) fn elem(&self) -> TypeId {
+        self.elem
+    }
 }
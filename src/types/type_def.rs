@@ -1,6 +1,5 @@
-use std::rc::Rc;
-use std::collections::HashMap;
-use super::{WireTypeEnum, TypeId};
+use std::sync::Arc;
+use super::{WireTypeEnum, TypeId, TypeMap};
 use super::ids::*;
 
 #[derive(Debug,Clone)]
@@ -21,7 +20,7 @@ pub enum TypeDef {
     FieldType,
     FieldTypeSlice,
     MapType,
-    Custom(Rc<WireTypeEnum>),
+    Custom(Arc<WireTypeEnum>),
 }
 
 impl TypeDef {
@@ -47,7 +46,46 @@ impl TypeDef {
         }
     }
 
-    pub fn from_id(type_id: TypeId, types: &HashMap<TypeId, TypeDef>) -> Option<TypeDef>{
+    /// Returns the name Go uses for the type
+    pub fn name(&self) -> &str {
+        match *self {
+            TypeDef::Bool => "bool",
+            TypeDef::Int => "int",
+            TypeDef::Uint => "uint",
+            TypeDef::Float => "float64",
+            TypeDef::ByteSlice => "[]byte",
+            TypeDef::String => "string",
+            TypeDef::Complex => "complex128",
+            TypeDef::Interface => "interface {}",
+            TypeDef::WireType => "wireType",
+            TypeDef::ArrayType => "arrayType",
+            TypeDef::CommonType => "CommonType",
+            TypeDef::SliceType => "sliceType",
+            TypeDef::StructType => "structType",
+            TypeDef::FieldType => "fieldType",
+            TypeDef::FieldTypeSlice => "[]*fieldType",
+            TypeDef::MapType => "mapType",
+            TypeDef::Custom(ref t) => t.name(),
+        }
+    }
+
+    /// Whether values of this type are encoded as a struct
+    pub fn is_struct(&self) -> bool {
+        match *self {
+            TypeDef::Custom(ref t) => matches!(**t, WireTypeEnum::Struct(_)),
+            _ => matches!(*self,
+                  TypeDef::WireType
+                | TypeDef::ArrayType
+                | TypeDef::CommonType
+                | TypeDef::SliceType
+                | TypeDef::StructType
+                | TypeDef::FieldType
+                | TypeDef::MapType
+            ),
+        }
+    }
+
+    pub fn from_id(type_id: TypeId, types: &TypeMap) -> Option<TypeDef> {
         Some(match type_id {
             BOOL_ID => TypeDef::Bool,
             INT_ID => TypeDef::Int,
@@ -65,7 +103,7 @@ impl TypeDef {
             FIELD_TYPE_ID => TypeDef::FieldType,
             FIELD_TYPE_SLICE_ID => TypeDef::FieldTypeSlice,
             MAP_TYPE_ID => TypeDef::MapType,
-            _ => return types.get(&type_id).cloned(),
+            _ => return types.get(type_id).cloned(),
         })
     }
 }
@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Read;
+use std::result;
+use std::sync::Arc;
+use serde::{Serialize,Serializer,Deserialize};
+use serde::de::{self,IgnoredAny,SeqAccess,Visitor};
+use de::{Deserializer,DeserializerOptions};
+use errors::*;
+use schema::TypeSchema;
+use super::{TypeId,TypeDef,WireType,WireTypeEnum};
+use super::ids::*;
+
+/// Ids below this are stored in a `Vec`, which covers the ids Go assigns in practice.
+/// Larger ids are kept in a `HashMap`, so that a single hostile id can't make
+/// the table allocate lots of memory.
+const MAX_DENSE_ID: TypeId = 4096;
+
+/// The types a gob stream has defined so far.
+///
+/// A `TypeMap` is an immutable snapshot: clones share the same table,
+/// and a deserializer that learns new types afterwards copies it first.
+/// Taking a snapshot with [`Deserializer::type_map`] is therefore cheap,
+/// and one snapshot can be used by any number of deserializers at once,
+/// including on other threads.
+///
+/// Type maps can be loaded ahead of time, to decode streams of values
+/// whose types were defined elsewhere: from a gob stream with
+/// [`TypeMap::from_reader`], or from any serde format,
+/// in which a type map is a list of Go `wireType`s.
+///
+/// ```no_run
+/// # extern crate gob;
+/// # use std::fs::File;
+/// # fn main() -> gob::Result<()> {
+/// let types = gob::TypeMap::from_reader(File::open("schema.gob")?)?;
+///
+/// let mut de = gob::Deserializer::new(File::open("values.gob")?);
+/// de.set_type_map(types);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Deserializer::type_map`]: struct.Deserializer.html#method.type_map
+/// [`TypeMap::from_reader`]: #method.from_reader
+#[derive(Debug,Clone,Default)]
+pub struct TypeMap {
+    table: Arc<Table>,
+}
+
+/// The custom types, along with their decode plans
+#[derive(Debug,Clone,Default)]
+struct Table {
+    dense: Vec<Option<Entry>>,
+    sparse: HashMap<TypeId, Entry>,
+    len: usize,
+}
+
+#[derive(Debug,Clone)]
+struct Entry {
+    type_def: TypeDef,
+    plan: Option<Arc<Plan>>,
+}
+
+/// How to decode values of a custom type, with all types it refers to resolved in advance
+#[derive(Debug)]
+pub enum Plan {
+    /// The fields of a struct, indexed by field number
+    Struct(Vec<FieldPlan>),
+    /// The element type of a slice or array
+    Elements(TypeDef),
+    /// The key and element types of a map
+    Entries(TypeDef, TypeDef),
+}
+
+#[derive(Debug)]
+pub struct FieldPlan {
+    pub name: String,
+    pub type_def: TypeDef,
+}
+
+impl TypeMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the type definitions of a gob stream. Values in the stream are skipped.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        Self::from_reader_with_options(reader, DeserializerOptions::default())
+    }
+
+    /// Like [`from_reader`](#method.from_reader), with other limits than the defaults.
+    pub fn from_reader_with_options<R: Read>(reader: R, options: DeserializerOptions) -> Result<Self> {
+        let mut de = Deserializer::with_options(reader, options);
+
+        while de.read_pending()? {
+            de.skip_pending()?;
+        }
+
+        Ok(de.type_map())
+    }
+
+    /// Number of custom types
+    pub fn len(&self) -> usize {
+        self.table.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Describes the custom types, ordered by id
+    pub fn types(&self) -> Vec<TypeSchema> {
+        self.type_ids().into_iter()
+            .filter_map(|type_id| self.get(type_id))
+            .map(TypeSchema::new)
+            .collect()
+    }
+
+    /// Describes the type with the given id, which may be a custom or a builtin type
+    pub fn schema(&self, type_id: TypeId) -> Option<TypeSchema> {
+        TypeDef::from_id(type_id, self).as_ref().map(TypeSchema::new)
+    }
+
+    /// The ids of the custom types, in ascending order
+    fn type_ids(&self) -> Vec<TypeId> {
+        let table = &self.table;
+        let mut type_ids = table.dense.iter().enumerate()
+            .filter(|&(_, entry)| entry.is_some())
+            .map(|(type_id, _)| type_id as TypeId)
+            .chain(table.sparse.keys().cloned())
+            .collect::<Vec<_>>();
+        type_ids.sort();
+        type_ids
+    }
+
+    fn entry(&self, type_id: TypeId) -> Option<&Entry> {
+        if (0..MAX_DENSE_ID).contains(&type_id) {
+            self.table.dense.get(type_id as usize).and_then(Option::as_ref)
+        } else {
+            self.table.sparse.get(&type_id)
+        }
+    }
+
+    fn entry_mut(&mut self, type_id: TypeId) -> Option<&mut Entry> {
+        let table = Arc::make_mut(&mut self.table);
+
+        if (0..MAX_DENSE_ID).contains(&type_id) {
+            table.dense.get_mut(type_id as usize).and_then(Option::as_mut)
+        } else {
+            table.sparse.get_mut(&type_id)
+        }
+    }
+
+    pub(crate) fn get(&self, type_id: TypeId) -> Option<&TypeDef> {
+        self.entry(type_id).map(|entry| &entry.type_def)
+    }
+
+    pub(crate) fn contains_key(&self, type_id: TypeId) -> bool {
+        self.entry(type_id).is_some()
+    }
+
+    /// The decode plan of the given type, if it has been compiled
+    pub(crate) fn plan(&self, type_id: TypeId) -> Option<&Arc<Plan>> {
+        self.entry(type_id).and_then(|entry| entry.plan.as_ref())
+    }
+
+    /// Adds the definition of a custom type and returns its id.
+    /// The type can't be used until the batch it belongs to is committed.
+    pub(crate) fn define(&mut self, wire_type: WireTypeEnum) -> Result<TypeId> {
+        let type_id = wire_type.id();
+
+        if is_type_builtin(type_id) {
+            bail!(Corruption::DefiningBuiltin(type_id))
+        }
+
+        if is_type_reserved(type_id) {
+            bail!(Corruption::DefiningReserved {
+                type_name: wire_type.name().into(),
+                type_id,
+            })
+        }
+
+        if self.contains_key(type_id) {
+            bail!(Corruption::TypeAlreadyDefined(type_id))
+        }
+
+        self.insert(type_id, TypeDef::Custom(Arc::new(wire_type)));
+        Ok(type_id)
+    }
+
+    /// Checks that a batch of freshly defined types only refers to builtin or defined types,
+    /// and compiles their decode plans. Types may refer to types defined later in the same batch,
+    /// so this can only happen once the batch is complete.
+    /// If any of them has a dangling reference, the whole batch is undefined again.
+    pub(crate) fn commit(&mut self, type_ids: &[TypeId]) -> Result<()> {
+        for &type_id in type_ids {
+            let dangling = match self.get(type_id) {
+                Some(TypeDef::Custom(wire_type)) => wire_type.references().into_iter()
+                    .find(|&(_, id)| !is_type_builtin(id) && !self.contains_key(id))
+                    .map(|(field, id)| Corruption::DanglingTypeReference {
+                        type_name: wire_type.name().into(),
+                        field: field.into(),
+                        type_id: id,
+                    }),
+                _ => None,
+            };
+
+            if let Some(err) = dangling {
+                self.undefine(type_ids);
+                bail!(err)
+            }
+        }
+
+        for &type_id in type_ids {
+            self.compile(type_id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes a batch of types again, e.g. because it was rejected
+    pub(crate) fn undefine(&mut self, type_ids: &[TypeId]) {
+        for &type_id in type_ids {
+            self.remove(type_id);
+        }
+    }
+
+    /// Adds a type, returning the previous definition of its id if there was one.
+    /// The type can't be decoded until `compile` has been called for it.
+    fn insert(&mut self, type_id: TypeId, type_def: TypeDef) -> Option<TypeDef> {
+        let entry = Entry { type_def, plan: None };
+        let table = Arc::make_mut(&mut self.table);
+
+        let previous = if (0..MAX_DENSE_ID).contains(&type_id) {
+            let index = type_id as usize;
+            if index >= table.dense.len() {
+                table.dense.resize(index + 1, None);
+            }
+            table.dense[index].replace(entry)
+        } else {
+            table.sparse.insert(type_id, entry)
+        };
+
+        if previous.is_none() {
+            table.len += 1;
+        }
+
+        previous.map(|entry| entry.type_def)
+    }
+
+    fn remove(&mut self, type_id: TypeId) -> Option<TypeDef> {
+        let table = Arc::make_mut(&mut self.table);
+
+        let removed = if (0..MAX_DENSE_ID).contains(&type_id) {
+            table.dense.get_mut(type_id as usize).and_then(Option::take)
+        } else {
+            table.sparse.remove(&type_id)
+        };
+
+        if removed.is_some() {
+            table.len -= 1;
+        }
+
+        removed.map(|entry| entry.type_def)
+    }
+
+    /// Builds the decode plan of a type
+    fn compile(&mut self, type_id: TypeId) -> Result<()> {
+        let wire_type = match self.get(type_id) {
+            Some(TypeDef::Custom(wire_type)) => wire_type.clone(),
+            _ => bail!(Corruption::UndefinedType(type_id)),
+        };
+
+        let resolve = |id| TypeDef::from_id(id, self).ok_or(Corruption::UndefinedType(id));
+
+        let plan = match *wire_type {
+            WireTypeEnum::Struct(ref t) => Plan::Struct(t.fields().iter()
+                .map(|field| Ok(FieldPlan {
+                    name: field.name().into(),
+                    type_def: resolve(field.id())?,
+                }))
+                .collect::<Result<_>>()?),
+            WireTypeEnum::Slice(ref t) => Plan::Elements(resolve(t.elem())?),
+            WireTypeEnum::Array(ref t) => Plan::Elements(resolve(t.elem())?),
+            WireTypeEnum::Map(ref t) => Plan::Entries(resolve(t.key())?, resolve(t.elem())?),
+        };
+
+        if let Some(entry) = self.entry_mut(type_id) {
+            entry.plan = Some(Arc::new(plan));
+        }
+
+        Ok(())
+    }
+}
+
+impl Serialize for TypeMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> result::Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.type_ids().into_iter().filter_map(|type_id| match self.get(type_id) {
+            Some(TypeDef::Custom(wire_type)) => Some(&**wire_type),
+            _ => None,
+        }))
+    }
+}
+
+/// Applies the default `DeserializerOptions` limits on the number of types and fields,
+/// as a type map read from a gob stream would.
+impl<'de> Deserialize<'de> for TypeMap {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> result::Result<Self, D::Error> {
+        deserializer.deserialize_seq(TypeMapVisitor(DeserializerOptions::default()))
+    }
+}
+
+struct TypeMapVisitor(DeserializerOptions);
+
+impl<'de> Visitor<'de> for TypeMapVisitor {
+    type Value = TypeMap;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a list of wireTypes")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> result::Result<TypeMap, A::Error> {
+        let options = self.0;
+        let mut types = TypeMap::new();
+        let mut defined = Vec::new();
+
+        loop {
+            if defined.len() >= options.max_types {
+                // Only an error if there is yet another type
+                match seq.next_element::<IgnoredAny>()? {
+                    Some(_) => return Err(de::Error::custom(Error::from(Limit::Types(options.max_types)))),
+                    None => break,
+                }
+            }
+
+            let wire_type = match seq.next_element::<WireType>()? {
+                Some(wire_type) => wire_type,
+                None => break,
+            };
+
+            let define = |types: &mut TypeMap| -> Result<TypeId> {
+                let wire_type = wire_type.try_into_enum()?;
+                if let WireTypeEnum::Struct(ref t) = wire_type {
+                    if t.fields().len() > options.max_fields {
+                        bail!(Limit::Fields(t.fields().len()))
+                    }
+                }
+                types.define(wire_type)
+            };
+            defined.push(define(&mut types).map_err(de::Error::custom)?);
+        }
+
+        types.commit(&defined).map_err(de::Error::custom)?;
+        Ok(types)
+    }
+}
+
+pub(crate) fn is_type_builtin(id: TypeId) -> bool {
+    [
+        BOOL_ID,
+        INT_ID,
+        UINT_ID,
+        FLOAT_ID,
+        BYTE_SLICE_ID,
+        STRING_ID,
+        COMPLEX_ID,
+        INTERFACE_ID,
+        WIRE_TYPE_ID,
+        ARRAY_TYPE_ID,
+        COMMON_TYPE_ID,
+        SLICE_TYPE_ID,
+        STRUCT_TYPE_ID,
+        FIELD_TYPE_ID,
+        FIELD_TYPE_SLICE_ID,
+        MAP_TYPE_ID,
+    ].contains(&id)
+}
+
+fn is_type_reserved(id: TypeId) -> bool {
+    (FIRST_RESERVED_ID..FIRST_USER_ID).contains(&id)
+}
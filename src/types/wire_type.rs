@@ -25,23 +25,24 @@ impl WireType {
         if map_type   .is_some() { n_some += 1 }
 
         if n_some != 1 {
-            bail!(ErrorKind::AmbiguousWireType)
+            bail!(Corruption::AmbiguousWireType)
         }
 
                     array_type .map(WireTypeEnum::Array)
         .or_else(|| slice_type .map(WireTypeEnum::Slice))
         .or_else(|| struct_type.map(WireTypeEnum::Struct))
         .or_else(|| map_type   .map(WireTypeEnum::Map))
-        .ok_or("BUG: Unhandled WireType case".into())
+        .ok_or_else(|| Corruption::AmbiguousWireType.into())
     }
 }
 
-#[derive(Debug,Clone)]
+/// Serializes like the `WireType` with the respective field set
+#[derive(Debug,Clone,Serialize)]
 pub enum WireTypeEnum {
-    Array(ArrayType),
-    Slice(SliceType),
-    Struct(StructType),
-    Map(MapType),
+    #[serde(rename="ArrayT")] Array(ArrayType),
+    #[serde(rename="SliceT")] Slice(SliceType),
+    #[serde(rename="StructT")] Struct(StructType),
+    #[serde(rename="MapT")] Map(MapType),
 }
 
 impl WireTypeEnum {
@@ -53,4 +54,24 @@ impl WireTypeEnum {
             WireTypeEnum::Map(ref t) => t.common.id,
         }
     }
+
+    pub fn name(&self) -> &str {
+        match *self {
+            WireTypeEnum::Array(ref t) => &t.common.name,
+            WireTypeEnum::Slice(ref t) => &t.common.name,
+            WireTypeEnum::Struct(ref t) => &t.common.name,
+            WireTypeEnum::Map(ref t) => &t.common.name,
+        }
+    }
+
+    /// Returns the ids of all types this type refers to,
+    /// together with the name of the field referring to them.
+    pub fn references(&self) -> Vec<(&str, TypeId)> {
+        match *self {
+            WireTypeEnum::Array(ref t) => vec![("Elem", t.elem)],
+            WireTypeEnum::Slice(ref t) => vec![("Elem", t.elem)],
+            WireTypeEnum::Struct(ref t) => t.fields.iter().map(|field| (field.name(), field.id())).collect(),
+            WireTypeEnum::Map(ref t) => vec![("Key", t.key), ("Elem", t.elem)],
+        }
+    }
 }
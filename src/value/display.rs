@@ -0,0 +1,200 @@
+use std::cmp::Ordering;
+use std::fmt::{self,Write};
+use super::Value;
+
+/// Prints the value the way Go's `fmt` does:
+/// `{}` like `%+v`, e.g. `{Name:gopher Tags:[a b]}`,
+/// and `{:#}` like `%#v`, e.g. `main.Record{Name:"gopher", Tags:[]string{"a", "b"}}`.
+///
+/// Map entries are sorted by key, as Go prints them.
+/// Unlike Go, struct fields left out of the stream for holding zero values aren't printed,
+/// and integers and floats are printed without the names of their Go types.
+///
+/// ```
+/// # use gob::Value;
+/// let point = Value::Struct {
+///     type_name: "main.Point".into(),
+///     fields: vec![("X".into(), Value::Int(1)), ("Label".into(), Value::String("a".into()))],
+/// };
+/// assert_eq!(point.to_string(), "{X:1 Label:a}");
+/// assert_eq!(format!("{:#}", point), r#"main.Point{X:1, Label:"a"}"#);
+/// ```
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let go_syntax = f.alternate();
+        self.write_go(f, go_syntax)
+    }
+}
+
+impl Value {
+    fn write_go(&self, f: &mut fmt::Formatter, go_syntax: bool) -> fmt::Result {
+        match *self {
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Uint(n) if go_syntax => write!(f, "{:#x}", n),
+            Value::Uint(n) => write!(f, "{}", n),
+            Value::Float(x) => write_float(f, x, false),
+            Value::Complex(re, im) => {
+                f.write_char('(')?;
+                write_float(f, re, false)?;
+                write_float(f, im, true)?;
+                f.write_str("i)")
+            }
+            Value::Bytes(ref bytes) if go_syntax => {
+                f.write_str("[]byte{")?;
+                for (i, b) in bytes.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{:#x}", b)?;
+                }
+                f.write_char('}')
+            }
+            Value::Bytes(ref bytes) => {
+                f.write_char('[')?;
+                for (i, b) in bytes.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(' ')?;
+                    }
+                    write!(f, "{}", b)?;
+                }
+                f.write_char(']')
+            }
+            Value::String(ref s) if go_syntax => write_quoted(f, s),
+            Value::String(ref s) => f.write_str(s),
+            Value::Slice { ref type_name, ref elems } | Value::Array { ref type_name, ref elems } => {
+                open(f, type_name, go_syntax, "[")?;
+                for (i, elem) in elems.iter().enumerate() {
+                    separate(f, i, go_syntax)?;
+                    elem.write_go(f, go_syntax)?;
+                }
+                close(f, go_syntax, "]")
+            }
+            Value::Map { ref type_name, ref entries } => {
+                let mut entries = entries.iter().collect::<Vec<_>>();
+                entries.sort_by(|a, b| compare_keys(&a.0, &b.0));
+
+                open(f, type_name, go_syntax, "map[")?;
+                for (i, &(key, elem)) in entries.iter().enumerate() {
+                    separate(f, i, go_syntax)?;
+                    key.write_go(f, go_syntax)?;
+                    f.write_char(':')?;
+                    elem.write_go(f, go_syntax)?;
+                }
+                close(f, go_syntax, "]")
+            }
+            Value::Struct { ref type_name, ref fields } => {
+                open(f, type_name, go_syntax, "{")?;
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    separate(f, i, go_syntax)?;
+                    write!(f, "{}:", name)?;
+                    value.write_go(f, go_syntax)?;
+                }
+                close(f, go_syntax, "}")
+            }
+            Value::Interface(Some(ref interface)) => interface.value.write_go(f, go_syntax),
+            Value::Interface(None) if go_syntax => f.write_str("interface {}(nil)"),
+            Value::Interface(None) => f.write_str("<nil>"),
+        }
+    }
+}
+
+/// `%#v` starts composite values with their type name, `%+v` with a bracket
+fn open(f: &mut fmt::Formatter, type_name: &str, go_syntax: bool, bracket: &str) -> fmt::Result {
+    if go_syntax {
+        write!(f, "{}{{", type_name)
+    } else {
+        f.write_str(bracket)
+    }
+}
+
+fn close(f: &mut fmt::Formatter, go_syntax: bool, bracket: &str) -> fmt::Result {
+    f.write_str(if go_syntax { "}" } else { bracket })
+}
+
+fn separate(f: &mut fmt::Formatter, index: usize, go_syntax: bool) -> fmt::Result {
+    match index {
+        0 => Ok(()),
+        _ if go_syntax => f.write_str(", "),
+        _ => f.write_char(' '),
+    }
+}
+
+/// Formats a float like Go's `%v`, which uses the shortest representation
+/// and switches to an exponent outside of `1e-4 <= |x| < 1e6`
+fn write_float(f: &mut fmt::Formatter, x: f64, signed: bool) -> fmt::Result {
+    if x.is_nan() {
+        return f.write_str(if signed { "+NaN" } else { "NaN" });
+    }
+    if x.is_infinite() {
+        return f.write_str(if x > 0.0 { "+Inf" } else { "-Inf" });
+    }
+    if signed && x.is_sign_positive() {
+        f.write_char('+')?;
+    }
+
+    let scientific = format!("{:e}", x);
+    let (mantissa, exp) = scientific.split_at(scientific.find('e').unwrap());
+    let exp: i32 = exp[1..].parse().unwrap();
+
+    if x != 0.0 && !(-4..6).contains(&exp) {
+        write!(f, "{}e{}{:02}", mantissa, if exp < 0 { '-' } else { '+' }, exp.abs())
+    } else {
+        write!(f, "{}", x)
+    }
+}
+
+/// Quotes a string like Go's `strconv.Quote`
+fn write_quoted(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '\x07' => f.write_str("\\a")?,
+            '\x08' => f.write_str("\\b")?,
+            '\x0c' => f.write_str("\\f")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            '\x0b' => f.write_str("\\v")?,
+            '\\' => f.write_str("\\\\")?,
+            '"' => f.write_str("\\\"")?,
+            c if c < ' ' || c == '\x7f' => write!(f, "\\x{:02x}", c as u32)?,
+            c if c.is_control() || (c.is_whitespace() && c != ' ') => {
+                if (c as u32) < 0x10000 {
+                    write!(f, "\\u{:04x}", c as u32)?
+                } else {
+                    write!(f, "\\U{:08x}", c as u32)?
+                }
+            }
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Orders map keys like Go's `fmt`: by kind first, then by value, with NaN first among floats.
+/// Keys of other kinds keep their order in the stream.
+fn compare_keys(a: &Value, b: &Value) -> Ordering {
+    fn rank(key: &Value) -> u8 {
+        match *key {
+            Value::Bool(_) => 0,
+            Value::Int(_) => 1,
+            Value::Uint(_) => 2,
+            Value::Float(_) => 3,
+            Value::String(_) => 4,
+            _ => 5,
+        }
+    }
+
+    let (a, b) = (a.concrete(), b.concrete());
+    match (a, b) {
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        (Value::Int(a), Value::Int(b)) => a.cmp(b),
+        (Value::Uint(a), Value::Uint(b)) => a.cmp(b),
+        (Value::Float(a), Value::Float(b)) => {
+            a.partial_cmp(b).unwrap_or_else(|| b.is_nan().cmp(&a.is_nan()))
+        }
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
@@ -0,0 +1,141 @@
+use std::ops;
+use super::Value;
+
+/// A way to look up a part of a `Value`, used by [`Value::get`] and `value[index]`.
+///
+/// Positions (`usize`) index into slices and arrays, names (`str`) into structs.
+/// Both look up map entries, by comparing the index with string, integer and bool keys.
+/// Interface values are looked through, so the index applies to their concrete value.
+///
+/// [`Value::get`]: enum.Value.html#method.get
+pub trait ValueIndex: private::Sealed {
+    #[doc(hidden)]
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value>;
+}
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for usize {}
+    impl Sealed for str {}
+    impl Sealed for String {}
+    impl<T: ?Sized + Sealed> Sealed for &T {}
+}
+
+impl ValueIndex for usize {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        match *value.concrete() {
+            Value::Slice { ref elems, .. } | Value::Array { ref elems, .. } => elems.get(*self),
+            Value::Map { ref entries, .. } => map_entry(entries, &self.to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl ValueIndex for str {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        match *value.concrete() {
+            Value::Struct { ref fields, .. } => {
+                fields.iter().find(|(name, _)| name == self).map(|(_, value)| value)
+            }
+            Value::Map { ref entries, .. } => map_entry(entries, self),
+            _ => None,
+        }
+    }
+}
+
+impl ValueIndex for String {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        self.as_str().index_into(value)
+    }
+}
+
+impl<T: ?Sized + ValueIndex> ValueIndex for &T {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        (**self).index_into(value)
+    }
+}
+
+fn map_entry<'v>(entries: &'v [(Value, Value)], key: &str) -> Option<&'v Value> {
+    entries.iter().find(|(k, _)| key_matches(k, key)).map(|(_, value)| value)
+}
+
+fn key_matches(key: &Value, token: &str) -> bool {
+    match *key.concrete() {
+        Value::String(ref s) => s == token,
+        Value::Int(n) => token.parse() == Ok(n),
+        Value::Uint(n) => token.parse() == Ok(n),
+        Value::Bool(b) => token.parse() == Ok(b),
+        _ => false,
+    }
+}
+
+impl Value {
+    /// Looks up an element of a slice or array, a field of a struct, or an entry of a map.
+    /// Returns `None` if there's no such part, e.g. because the field was left out
+    /// for holding a zero value.
+    ///
+    /// ```
+    /// # use gob::Value;
+    /// let point = Value::Struct {
+    ///     type_name: "main.Point".into(),
+    ///     fields: vec![("X".into(), Value::Int(1))],
+    /// };
+    /// assert_eq!(point.get("X"), Some(&Value::Int(1)));
+    /// assert_eq!(point.get("Y"), None);
+    /// assert_eq!(point["X"], Value::Int(1));
+    /// ```
+    pub fn get<I: ValueIndex>(&self, index: I) -> Option<&Value> {
+        index.index_into(self)
+    }
+
+    /// Looks up a nested value by a JSON pointer (RFC 6901), like `/Items/3/Price`.
+    ///
+    /// Each part of the pointer is applied like an index passed to [`get`],
+    /// with numeric parts indexing into slices and arrays.
+    /// `~1` and `~0` stand for `/` and `~` in struct field names and map keys.
+    ///
+    /// [`get`]: #method.get
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        if !pointer.starts_with('/') {
+            return None;
+        }
+
+        pointer[1..].split('/').try_fold(self, |value, token| {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            match *value.concrete() {
+                Value::Slice { .. } | Value::Array { .. } => parse_position(&token).and_then(|i| value.get(i)),
+                _ => value.get(&token),
+            }
+        })
+    }
+}
+
+/// Parses a position as RFC 6901 writes it, without signs or leading zeros
+fn parse_position(token: &str) -> Option<usize> {
+    if token.starts_with('+') || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+/// Indexing a `Value` panics if there's no such part, unlike `serde_json::Value`,
+/// which returns `Null` instead. Use [`Value::get`] for parts that may be missing,
+/// like struct fields Go leaves out for holding zero values.
+///
+/// [`Value::get`]: enum.Value.html#method.get
+impl<I: ValueIndex> ops::Index<I> for Value {
+    type Output = Value;
+
+    /// Looks up a part of the value like [`get`], panicking if there's no such part
+    ///
+    /// [`get`]: #method.get
+    fn index(&self, index: I) -> &Value {
+        match index.index_into(self) {
+            Some(value) => value,
+            None => panic!("no such element in value of type {}", self.type_name()),
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! Gob values decoded without knowing their types in advance.
+
+use std::cell::RefCell;
+use std::fmt;
+use serde::de::{self,Deserialize,Visitor};
+
+mod display;
+mod index;
+
+pub use self::index::ValueIndex;
+
+/// Name of the newtype struct the deserializer recognizes as a request for a `Value`
+pub(crate) const TOKEN: &str = "$gob::private::Value";
+
+thread_local! {
+    /// A value decoded by the deserializer, waiting for `Value::deserialize` to take it
+    static DECODED: RefCell<Option<Value>> = const { RefCell::new(None) };
+}
+
+/// Hands a value the deserializer has decoded in place over to the visitor of `Value::deserialize`,
+/// since serde's data model has no way to carry it
+pub(crate) fn visit<'de, V: Visitor<'de>, E: de::Error>(value: Value, visitor: V) -> ::std::result::Result<V::Value, E> {
+    DECODED.with(|decoded| *decoded.borrow_mut() = Some(value));
+    let result = visitor.visit_unit();
+    DECODED.with(|decoded| decoded.borrow_mut().take());
+    result
+}
+
+/// Any gob value, decoded by following the type definitions of the stream.
+///
+/// Unlike decoding into a generic serde type like `serde_json::Value`,
+/// this keeps what Go knows about the value:
+/// the names of struct, slice, array and map types, signed and unsigned integers,
+/// complex numbers, and the concrete types of interface values.
+///
+/// Go leaves out struct fields holding zero values, and so does `Value`.
+/// Strings that aren't valid UTF-8 have their invalid sequences replaced.
+///
+/// Go's types are only kept when gob's `Deserializer` decodes the `Value` itself,
+/// as the value being deserialized or as a field of it.
+/// Anywhere else, like in other formats or in `untagged` enums and `flatten`ed fields,
+/// for which serde buffers the input, a `Value` is built from serde's data model:
+/// sequences become slices of type `[]interface {}`, structs and maps become maps of type
+/// `map[interface {}]interface {}`, and nil or unit values become `Interface(None)`.
+/// Gob's interface values and complex numbers can't be buffered by serde at all.
+///
+/// ```no_run
+/// # extern crate gob;
+/// # use std::fs::File;
+/// # fn main() -> gob::Result<()> {
+/// let mut de = gob::Deserializer::new(File::open("unknown.gob")?);
+///
+/// if let gob::Value::Struct { type_name, fields } = de.deserialize()? {
+///     for (name, value) in fields {
+///         println!("{}.{} = {:?}", type_name, name, value);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug,Clone,PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    Uint(u64),
+    Float(f64),
+    /// The real and imaginary parts of a complex number
+    Complex(f64, f64),
+    Bytes(Vec<u8>),
+    String(String),
+    Slice {
+        type_name: String,
+        elems: Vec<Value>,
+    },
+    /// An array, which always holds as many elements as its type specifies
+    Array {
+        type_name: String,
+        elems: Vec<Value>,
+    },
+    /// A map, with its entries in stream order
+    Map {
+        type_name: String,
+        entries: Vec<(Value, Value)>,
+    },
+    /// A struct, with the fields sent in stream order
+    Struct {
+        type_name: String,
+        fields: Vec<(String, Value)>,
+    },
+    /// An interface value, or `None` if it's nil
+    Interface(Option<Box<Interface>>),
+}
+
+/// The concrete value held by an interface value
+#[derive(Debug,Clone,PartialEq)]
+pub struct Interface {
+    /// The name the concrete type was registered under with Go's `gob.Register`
+    pub name: String,
+    pub value: Value,
+}
+
+impl Value {
+    /// The Go name of the value's type, e.g. `int` or `main.Point`.
+    /// For interface values, this is the name of the concrete type.
+    ///
+    /// Gob doesn't tell apart integers or floats of different sizes,
+    /// so these are named like the types gob uses for them on the wire.
+    pub fn type_name(&self) -> &str {
+        match *self {
+            Value::Bool(_) => "bool",
+            Value::Int(_) => "int",
+            Value::Uint(_) => "uint",
+            Value::Float(_) => "float64",
+            Value::Complex(..) => "complex128",
+            Value::Bytes(_) => "[]byte",
+            Value::String(_) => "string",
+            Value::Slice { ref type_name, .. }
+            | Value::Array { ref type_name, .. }
+            | Value::Map { ref type_name, .. }
+            | Value::Struct { ref type_name, .. } => type_name,
+            Value::Interface(Some(ref interface)) => &interface.name,
+            Value::Interface(None) => "interface {}",
+        }
+    }
+
+    /// The value held by an interface value, or the value itself for other values
+    fn concrete(&self) -> &Value {
+        match *self {
+            Value::Interface(Some(ref interface)) => interface.value.concrete(),
+            ref value => value,
+        }
+    }
+}
+
+/// Builds a `Value` from serde's data model, which lacks Go's type names.
+/// Values decoded by gob's own deserializer are handed over whole through `visit_unit`.
+struct ValueVisitor;
+
+/// The Go types of sequences and maps that reach a `Value` through serde's data model
+const SLICE_TYPE_NAME: &str = "[]interface {}";
+const MAP_TYPE_NAME: &str = "map[interface {}]interface {}";
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "any value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> ::std::result::Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> ::std::result::Result<Value, E> {
+        Ok(Value::Int(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> ::std::result::Result<Value, E> {
+        Ok(Value::Uint(v))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> ::std::result::Result<Value, E> {
+        Ok(Value::Float(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> ::std::result::Result<Value, E> {
+        Ok(Value::String(v.into()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> ::std::result::Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> ::std::result::Result<Value, E> {
+        Ok(Value::Bytes(v.into()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> ::std::result::Result<Value, E> {
+        Ok(Value::Bytes(v))
+    }
+
+    fn visit_none<E: de::Error>(self) -> ::std::result::Result<Value, E> {
+        Ok(Value::Interface(None))
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, deserializer: D) -> ::std::result::Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    /// Takes the value handed over by `visit`, or stands for nil otherwise
+    fn visit_unit<E: de::Error>(self) -> ::std::result::Result<Value, E> {
+        Ok(DECODED.with(|decoded| decoded.borrow_mut().take()).unwrap_or(Value::Interface(None)))
+    }
+
+    /// Reached when `TOKEN` isn't recognized, so the content mustn't be asked for a `Value` again
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(self, deserializer: D) -> ::std::result::Result<Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> ::std::result::Result<Value, A::Error> {
+        let mut elems = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(elem) = seq.next_element()? {
+            elems.push(elem);
+        }
+
+        Ok(Value::Slice { type_name: SLICE_TYPE_NAME.into(), elems })
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> ::std::result::Result<Value, A::Error> {
+        let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+
+        Ok(Value::Map { type_name: MAP_TYPE_NAME.into(), entries })
+    }
+}
+
+/// Gob's deserializer decodes a `Value` in place, keeping Go's types.
+/// Any other deserializer, or serde buffering the input for `untagged` enums or `flatten`,
+/// goes through serde's data model instead, as described on [`Value`].
+///
+/// [`Value`]: enum.Value.html
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        deserializer.deserialize_newtype_struct(TOKEN, ValueVisitor)
+    }
+}
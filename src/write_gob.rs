@@ -0,0 +1,48 @@
+use std::io::{self,Write};
+use types::TypeId;
+
+/// Counterpart of `ReadGob`, for re-encoding type definitions
+pub trait WriteGob: Write {
+    fn write_gob_u64(&mut self, n: u64) -> io::Result<()> {
+        if n < 128 {
+            return self.write_all(&[n as u8]);
+        }
+
+        let bytes = n.to_be_bytes();
+        let skip = (n.leading_zeros() / 8) as usize;
+        let n_bytes = bytes.len() - skip;
+
+        self.write_all(&[(-(n_bytes as i8)) as u8])?;
+        self.write_all(&bytes[skip..])
+    }
+
+    fn write_gob_usize(&mut self, n: usize) -> io::Result<()> {
+        self.write_gob_u64(n as u64)
+    }
+
+    fn write_gob_i64(&mut self, n: i64) -> io::Result<()> {
+        let n = if n < 0 {
+            (!n as u64) << 1 | 1
+        } else {
+            (n as u64) << 1
+        };
+
+        self.write_gob_u64(n)
+    }
+
+    fn write_gob_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.write_gob_usize(bytes.len())?;
+        self.write_all(bytes)
+    }
+
+    fn write_gob_type_id(&mut self, type_id: TypeId) -> io::Result<()> {
+        self.write_gob_i64(type_id)
+    }
+
+    /// Writes a message, prefixed by its length
+    fn write_gob_message(&mut self, message: &[u8]) -> io::Result<()> {
+        self.write_gob_bytes(message)
+    }
+}
+
+impl<W: Write> WriteGob for W {}
@@ -0,0 +1,173 @@
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context,Poll,Waker};
+use gob::{AsyncDeserializer,DeserializerOptions,ErrorKind,Limit};
+use tokio::io::{self as tokio_io,AsyncWriteExt,DuplexStream};
+use tokio::runtime::{self,Runtime};
+use utils::wire::{self,Point,Stream,points};
+
+fn runtime() -> Runtime {
+    runtime::Builder::new_current_thread().build().unwrap()
+}
+
+fn write(runtime: &Runtime, writer: &mut DuplexStream, bytes: &[u8]) {
+    runtime.block_on(writer.write_all(bytes)).unwrap();
+}
+
+fn poll_once<F: Future + Unpin>(future: &mut F) -> Poll<F::Output> {
+    let mut cx = Context::from_waker(Waker::noop());
+    Pin::new(future).poll(&mut cx)
+}
+
+#[test]
+fn values() {
+    let runtime = runtime();
+    let (mut writer, reader) = tokio_io::duplex(1024);
+    write(&runtime, &mut writer, &points().bytes);
+    drop(writer);
+
+    let mut de = AsyncDeserializer::new(reader);
+    assert_eq!(runtime.block_on(de.next::<Point>()).unwrap(), Some(Point { x: 1, y: 2 }));
+    assert_eq!(runtime.block_on(de.next::<Point>()).unwrap(), Some(Point { x: -3, y: 4 }));
+    assert_eq!(runtime.block_on(de.next::<Point>()).unwrap(), None);
+    assert_eq!(de.offset(), points().bytes.len() as u64);
+}
+
+#[test]
+fn undecoded_bytes_returned() {
+    let runtime = runtime();
+    let (mut writer, reader) = tokio_io::duplex(1024);
+    let bytes = points().bytes;
+    write(&runtime, &mut writer, &bytes);
+
+    let mut de = AsyncDeserializer::new(reader);
+    assert_eq!(runtime.block_on(de.next::<Point>()).unwrap(), Some(Point { x: 1, y: 2 }));
+
+    let offset = de.offset() as usize;
+    let (_, rest) = de.into_inner();
+    assert_eq!(rest, &bytes[offset..]);
+}
+
+#[test]
+fn waits_for_whole_value() {
+    let runtime = runtime();
+    let bytes = points().bytes;
+    let (mut writer, reader) = tokio_io::duplex(1024);
+    let mut de = AsyncDeserializer::new(reader);
+
+    // Feed the stream a byte at a time, so every message arrives in pieces
+    let mut values = Vec::new();
+    for byte in &bytes {
+        write(&runtime, &mut writer, &[*byte]);
+
+        let mut next = de.next::<Point>();
+        while let Poll::Ready(value) = poll_once(&mut next) {
+            values.push(value.unwrap().unwrap());
+        }
+    }
+
+    assert_eq!(values, vec![Point { x: 1, y: 2 }, Point { x: -3, y: 4 }]);
+}
+
+#[test]
+fn cancelled_between_reads() {
+    let runtime = runtime();
+    let bytes = points().bytes;
+    let split = bytes.len() - 3;
+    let (mut writer, reader) = tokio_io::duplex(1024);
+    let mut de = AsyncDeserializer::new(reader);
+
+    write(&runtime, &mut writer, &bytes[..split]);
+
+    let first = {
+        let mut next = de.next::<Point>();
+        let first = match poll_once(&mut next) {
+            Poll::Ready(value) => value.unwrap(),
+            Poll::Pending => panic!("the first value was complete"),
+        };
+
+        // Dropped while the second value has only been read partly
+        assert!(poll_once(&mut next).is_pending());
+        first
+    };
+    assert_eq!(first, Some(Point { x: 1, y: 2 }));
+
+    write(&runtime, &mut writer, &bytes[split..]);
+    drop(writer);
+
+    assert_eq!(runtime.block_on(de.next::<Point>()).unwrap(), Some(Point { x: -3, y: 4 }));
+    assert_eq!(runtime.block_on(de.next::<Point>()).unwrap(), None);
+}
+
+#[test]
+fn trailing_definitions() {
+    let runtime = runtime();
+    let (mut writer, reader) = tokio_io::duplex(1024);
+    write(&runtime, &mut writer, &Stream::new().slice(65, "[]int", 2).bytes);
+    drop(writer);
+
+    let mut de = AsyncDeserializer::new(reader);
+    assert_eq!(runtime.block_on(de.next::<Vec<i64>>()).unwrap(), None);
+    assert_eq!(de.type_map().len(), 1);
+}
+
+#[test]
+fn partial_message_at_end() {
+    let runtime = runtime();
+    let bytes = points().bytes;
+    let (mut writer, reader) = tokio_io::duplex(1024);
+    write(&runtime, &mut writer, &bytes[..bytes.len() - 1]);
+    drop(writer);
+
+    let mut de = AsyncDeserializer::new(reader);
+    runtime.block_on(de.next::<Point>()).unwrap();
+
+    let err = runtime.block_on(de.next::<Point>()).unwrap_err();
+    match *err.kind() {
+        ErrorKind::Io(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => {},
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn message_size() {
+    let runtime = runtime();
+    let (mut writer, reader) = tokio_io::duplex(1024);
+
+    // Only the length of the message is sent, so the limit has to be checked before the body arrives
+    let mut header = Vec::new();
+    wire::uint(&mut header, 1 << 20);
+    write(&runtime, &mut writer, &header);
+
+    let options = DeserializerOptions::new().max_message_size(1024);
+    let mut de = AsyncDeserializer::with_options(reader, options);
+    let err = runtime.block_on(de.next::<Point>()).unwrap_err();
+    match *err.kind() {
+        ErrorKind::LimitExceeded(Limit::MessageSize(len)) => assert_eq!(len, 1 << 20),
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn definitions_only() {
+    let runtime = runtime();
+    let (mut writer, reader) = tokio_io::duplex(1024);
+
+    // A peer sending definitions without values must not make the buffer grow forever
+    let stream = Stream::new()
+        .slice(65, "[]int", 2)
+        .slice(66, "[]string", 6)
+        .slice(67, "[]bool", 1);
+    write(&runtime, &mut writer, &stream.bytes);
+
+    let options = DeserializerOptions::new().max_types(2);
+    let mut de = AsyncDeserializer::with_options(reader, options);
+    match poll_once(&mut de.next::<Point>()) {
+        Poll::Ready(Err(ref err)) => match *err.kind() {
+            ErrorKind::LimitExceeded(Limit::Types(2)) => {},
+            ref kind => panic!("unexpected error: {:?}", kind),
+        },
+        poll => panic!("unexpected poll result: {:?}", poll),
+    }
+}
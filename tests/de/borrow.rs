@@ -0,0 +1,178 @@
+use std::borrow::Cow;
+use serde::Deserialize;
+use gob::{Deserializer,ErrorKind,Index,Projection,RawValue};
+use utils::wire::{self,Stream};
+
+#[derive(Deserialize,Debug,PartialEq)]
+struct Record<'a> {
+    #[serde(rename="Name")] name: &'a str,
+    #[serde(rename="Data")] data: &'a [u8],
+    #[serde(rename="Note", borrow)] note: Cow<'a, str>,
+}
+
+fn record(name: &str, data: &[u8]) -> Vec<u8> {
+    let mut record = Vec::new();
+    wire::uint(&mut record, 1);
+    wire::string(&mut record, name);
+    wire::uint(&mut record, 1);
+    wire::uint(&mut record, data.len() as u64);
+    record.extend_from_slice(data);
+    wire::uint(&mut record, 1);
+    wire::string(&mut record, "note");
+    wire::uint(&mut record, 0);
+    record
+}
+
+fn records() -> Stream {
+    Stream::new()
+        .structure(65, "Record", &[("Name", 6), ("Data", 5), ("Note", 6)])
+        .value(65, &record("first", &[1, 2, 3]))
+        .value(65, &record("second", &[0xff]))
+}
+
+fn contains(bytes: &[u8], part: &[u8]) -> bool {
+    let range = bytes.as_ptr_range();
+    range.start <= part.as_ptr() && part.as_ptr_range().end <= range.end
+}
+
+#[test]
+fn borrowed_fields() {
+    let stream = records();
+    let mut de = Deserializer::from_slice(&stream.bytes);
+
+    let first: Record = de.deserialize().unwrap();
+    let second: Record = de.deserialize().unwrap();
+
+    assert_eq!(first.name, "first");
+    assert_eq!(first.data, &[1, 2, 3]);
+    assert_eq!(second.name, "second");
+    assert_eq!(second.data, &[0xff]);
+
+    for record in &[&first, &second] {
+        assert!(contains(&stream.bytes, record.name.as_bytes()));
+        assert!(contains(&stream.bytes, record.data));
+        match record.note {
+            Cow::Borrowed(note) => assert!(contains(&stream.bytes, note.as_bytes())),
+            Cow::Owned(_) => panic!("note wasn't borrowed"),
+        }
+    }
+
+    assert_eq!(de.offset(), stream.bytes.len() as u64);
+}
+
+#[derive(Deserialize,Debug,PartialEq)]
+struct Owned {
+    #[serde(rename="Name")] name: String,
+    #[serde(rename="Data", with="::serde_bytes")] data: Vec<u8>,
+}
+
+#[test]
+fn iterated_borrowed_values() {
+    let stream = records();
+    let records = Deserializer::from_slice(&stream.bytes)
+        .into_iter::<Record>()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(records.iter().map(|record| record.name).collect::<Vec<_>>(), vec!["first", "second"]);
+    assert!(records.iter().all(|record| contains(&stream.bytes, record.data)));
+
+    let mut de = Deserializer::from_slice(&stream.bytes);
+    assert_eq!(de.iter::<Record>().next().unwrap().unwrap().name, "first");
+    assert_eq!(de.deserialize::<Record>().unwrap().name, "second");
+}
+
+#[test]
+fn owned_values_from_slice() {
+    let stream = records();
+    let values = Deserializer::from_slice(&stream.bytes)
+        .into_iter::<Owned>()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(values[1], Owned { name: "second".into(), data: vec![0xff] });
+}
+
+#[test]
+fn invalid_utf8() {
+    let mut value = Vec::new();
+    wire::uint(&mut value, 0);
+    wire::uint(&mut value, 2);
+    value.extend_from_slice(&[0xc3, 0x28]);
+    let stream = Stream::new().value(6, &value);
+
+    let err = Deserializer::from_slice(&stream.bytes).deserialize::<&str>().unwrap_err();
+
+    match *err.kind() {
+        ErrorKind::Custom(ref msg) => assert!(msg.contains("borrowed string"), "{}", msg),
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn projected_fields() {
+    #[derive(Deserialize)]
+    struct Name<'a> {
+        #[serde(rename="Name")] name: &'a str,
+    }
+
+    let stream = records();
+    let projection = Projection::new(["Name"]).unwrap();
+    let mut de = Deserializer::from_slice(&stream.bytes);
+
+    let name: Name = de.deserialize_projected(&projection).unwrap();
+    assert_eq!(name.name, "first");
+    assert!(contains(&stream.bytes, name.name.as_bytes()));
+}
+
+#[test]
+fn borrowed_from_reader() {
+    let stream = records();
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+
+    // `deserialize` only accepts owned types here, but serde can still be used directly
+    let err = Record::deserialize(&mut de).unwrap_err();
+
+    match *err.kind() {
+        ErrorKind::TypeMismatch { ref expected, .. } => assert_eq!(expected, "a borrowed string"),
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn truncated_string() {
+    let mut bytes = records().bytes;
+    bytes.truncate(bytes.len() - 4);
+
+    let mut de = Deserializer::from_slice(&bytes);
+    de.deserialize::<Record>().unwrap();
+    let err = de.deserialize::<Record>().unwrap_err();
+
+    match *err.kind() {
+        ErrorKind::Io(_) => {}
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn random_access() {
+    let stream = records();
+    let index = Index::build(::gob::de::SliceInput::new(&stream.bytes)).unwrap();
+    let mut de = Deserializer::from_slice(&stream.bytes);
+
+    de.seek_value(&index, 1).unwrap();
+    assert_eq!(de.deserialize::<Record>().unwrap().name, "second");
+
+    de.seek_value(&index, 0).unwrap();
+    assert_eq!(de.deserialize::<Record>().unwrap().name, "first");
+}
+
+#[test]
+fn raw_values() {
+    let stream = records();
+    let mut de = Deserializer::from_slice(&stream.bytes);
+
+    let raw: RawValue = de.deserialize().unwrap();
+    assert_eq!(raw.decode::<Owned>().unwrap().name, "first");
+    assert_eq!(de.deserialize::<Record>().unwrap().name, "second");
+}
@@ -0,0 +1,84 @@
+use std::io::Cursor;
+use gob::{Checkpoint,Deserializer,ErrorKind};
+use utils::wire::{Point,Stream,point};
+
+fn stream() -> Vec<u8> {
+    Stream::new()
+        .structure(65, "Point", &[("X", 2), ("Y", 2)])
+        .value(65, &point(1, 2))
+        .value(65, &point(3, 4))
+        .structure(66, "Line", &[("A", 65), ("B", 65)])
+        .value(65, &point(5, 6))
+        .bytes
+}
+
+#[test]
+fn resume_between_values() {
+    let bytes = stream();
+
+    let mut de = Deserializer::new(Cursor::new(&bytes));
+    assert_eq!(de.deserialize::<Point>().unwrap(), Point { x: 1, y: 2 });
+    let checkpoint = de.checkpoint();
+    assert_eq!(checkpoint.offset(), de.offset());
+    assert_eq!(checkpoint.type_map().len(), 1);
+
+    let mut de = Deserializer::resume(Cursor::new(&bytes), checkpoint).unwrap();
+    assert_eq!(de.deserialize::<Point>().unwrap(), Point { x: 3, y: 4 });
+    assert_eq!(de.deserialize::<Point>().unwrap(), Point { x: 5, y: 6 });
+    assert_eq!(de.type_map().len(), 2);
+    assert_eq!(de.offset(), bytes.len() as u64);
+}
+
+#[test]
+fn resume_after_peek() {
+    let bytes = stream();
+
+    let mut de = Deserializer::new(Cursor::new(&bytes));
+    de.deserialize::<Point>().unwrap();
+    de.deserialize::<Point>().unwrap();
+    assert!(de.peek_type().unwrap().is_some());
+    let checkpoint = de.checkpoint();
+
+    // The definition of Line precedes the value, so it's part of the checkpoint
+    assert_eq!(checkpoint.type_map().len(), 2);
+
+    let mut de = Deserializer::resume(Cursor::new(&bytes), checkpoint).unwrap();
+    assert_eq!(de.deserialize::<Point>().unwrap(), Point { x: 5, y: 6 });
+}
+
+#[test]
+fn serialized_checkpoint() {
+    let bytes = stream();
+
+    let mut de = Deserializer::new(Cursor::new(&bytes));
+    de.deserialize::<Point>().unwrap();
+    let json = ::serde_json::to_string(&de.checkpoint()).unwrap();
+
+    let checkpoint: Checkpoint = ::serde_json::from_str(&json).unwrap();
+    let mut de = Deserializer::resume(Cursor::new(&bytes), checkpoint).unwrap();
+    let points = de.iter::<Point>().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(points, vec![Point { x: 3, y: 4 }, Point { x: 5, y: 6 }]);
+}
+
+#[test]
+fn errors_report_stream_position() {
+    let mut bytes = stream();
+    bytes.truncate(bytes.len() - 2);
+
+    let mut de = Deserializer::new(Cursor::new(&bytes));
+    de.deserialize::<Point>().unwrap();
+    de.deserialize::<Point>().unwrap();
+    let checkpoint = de.checkpoint();
+
+    let mut de = Deserializer::resume(Cursor::new(&bytes), checkpoint).unwrap();
+    let err = de.deserialize::<Point>().unwrap_err();
+
+    match *err.kind() {
+        ErrorKind::Io(_) => {}
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+
+    let context = err.context().unwrap();
+    assert_eq!(context.offset(), bytes.len() as u64);
+    assert_eq!(context.message_index(), 4);
+}
@@ -0,0 +1,69 @@
+use std::io;
+use bytes::BytesMut;
+use futures::StreamExt;
+use gob::ErrorKind;
+use gob::codec::GobCodec;
+use tokio::io::{self as tokio_io,AsyncWriteExt};
+use tokio::runtime;
+use tokio_util::codec::{Decoder,FramedRead};
+use utils::wire::{Point,Stream,points};
+
+#[test]
+fn partial_frames() {
+    let mut codec = GobCodec::<Point>::new();
+    let mut buf = BytesMut::new();
+    let mut values = Vec::new();
+
+    for &byte in &points().bytes {
+        buf.extend_from_slice(&[byte]);
+
+        while let Some(value) = codec.decode(&mut buf).unwrap() {
+            values.push(value);
+        }
+    }
+
+    assert_eq!(values, vec![Point { x: 1, y: 2 }, Point { x: -3, y: 4 }]);
+    assert!(buf.is_empty());
+    assert_eq!(codec.type_map().len(), 1);
+    assert_eq!(codec.offset(), points().bytes.len() as u64);
+}
+
+#[test]
+fn framed_read() {
+    let runtime = runtime::Builder::new_current_thread().build().unwrap();
+    let (mut writer, reader) = tokio_io::duplex(1024);
+    runtime.block_on(writer.write_all(&points().bytes)).unwrap();
+    drop(writer);
+
+    let values = FramedRead::new(reader, GobCodec::<Point>::new())
+        .map(Result::unwrap)
+        .collect::<Vec<_>>();
+
+    assert_eq!(runtime.block_on(values), vec![Point { x: 1, y: 2 }, Point { x: -3, y: 4 }]);
+}
+
+#[test]
+fn trailing_definitions() {
+    let mut codec = GobCodec::<Vec<i64>>::new();
+    let mut buf = BytesMut::from(&Stream::new().slice(65, "[]int", 2).bytes[..]);
+
+    assert_eq!(codec.decode(&mut buf).unwrap(), None);
+    assert_eq!(codec.decode_eof(&mut buf).unwrap(), None);
+    assert!(buf.is_empty());
+    assert_eq!(codec.type_map().len(), 1);
+}
+
+#[test]
+fn partial_message_at_end() {
+    let bytes = points().bytes;
+    let mut codec = GobCodec::<Point>::new();
+    let mut buf = BytesMut::from(&bytes[..bytes.len() - 1]);
+
+    assert_eq!(codec.decode_eof(&mut buf).unwrap(), Some(Point { x: 1, y: 2 }));
+
+    let err = codec.decode_eof(&mut buf).unwrap_err();
+    match *err.kind() {
+        ErrorKind::Io(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => {},
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
@@ -0,0 +1,117 @@
+use std::error::Error;
+use gob::{Deserializer,ErrorKind};
+use utils::wire::{self,Stream};
+
+#[derive(Deserialize,Debug)]
+#[allow(dead_code)]
+struct Item {
+    #[serde(rename="Sku")] sku: String,
+    #[serde(rename="Price")] price: String,
+}
+
+#[derive(Deserialize,Debug)]
+#[allow(dead_code)]
+struct Order {
+    #[serde(rename="Items")] items: Vec<Item>,
+}
+
+#[test]
+fn nested_field_path() {
+    let mut value = Vec::new();
+    wire::uint(&mut value, 1);
+    wire::uint(&mut value, 2);
+    for &(sku, price) in &[("a", 1), ("b", 2)] {
+        wire::uint(&mut value, 1);
+        wire::string(&mut value, sku);
+        wire::uint(&mut value, 1);
+        wire::int(&mut value, price);
+        wire::uint(&mut value, 0);
+    }
+    wire::uint(&mut value, 0);
+
+    let stream = Stream::new()
+        .structure(65, "Order", &[("Items", 66)])
+        .slice(66, "[]Item", 67)
+        .structure(67, "Item", &[("Sku", 6), ("Price", 2)])
+        .value(65, &value);
+
+    let err = Deserializer::new(stream.bytes.as_slice()).deserialize::<Order>().unwrap_err();
+    let context = err.context().expect("error context");
+
+    assert_eq!(context.path(), "Order.Items[0].Price");
+    assert_eq!(context.type_name(), Some("int"));
+    assert_eq!(context.message_index(), 3);
+    assert!(context.offset() > 0 && context.offset() < stream.bytes.len() as u64);
+    assert!(err.to_string().contains("at Order.Items[0].Price (int) in message 3"));
+}
+
+#[test]
+fn type_definition() {
+    let stream = Stream::new()
+        .structure(65, "Foo", &[("Bar", 70)])
+        .value(65, &[0]);
+
+    let err = Deserializer::new(stream.bytes.as_slice()).deserialize::<Vec<i64>>().unwrap_err();
+    let context = err.context().expect("error context");
+
+    assert_eq!(context.path(), "");
+    assert_eq!(context.type_name(), None);
+    assert_eq!(context.message_index(), 1);
+    assert!(err.to_string().ends_with(&format!("70 in message 1, byte offset {}", context.offset())), "{}", err);
+}
+
+#[test]
+fn type_mismatch() {
+    let stream = Stream::new().value(6, &[0, 1, b'x']);
+
+    let err = Deserializer::new(stream.bytes.as_slice()).deserialize::<i64>().unwrap_err();
+    match *err.kind() {
+        ErrorKind::TypeMismatch { ref found, ref expected } => {
+            assert_eq!(found, "byte array");
+            assert_eq!(expected, "i64");
+        }
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn message_index_after_failed_value() {
+    let stream = Stream::new()
+        .value(6, &[0, 1, b'x'])
+        .value(6, &[0, 1, b'y']);
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+
+    let err = de.deserialize::<i64>().unwrap_err();
+    assert_eq!(err.context().unwrap().message_index(), 0);
+
+    let err = de.deserialize::<i64>().unwrap_err();
+    assert_eq!(err.context().unwrap().message_index(), 1);
+}
+
+#[test]
+fn unsupported() {
+    let stream = Stream::new().value(7, &[0, 0, 0]);
+
+    let err = Deserializer::new(stream.bytes.as_slice()).deserialize::<(f64, f64)>().unwrap_err();
+    match *err.kind() {
+        ErrorKind::Unsupported(_) => {},
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn io_source() {
+    let stream = Stream::new().value(2, &[0]);
+
+    let err = Deserializer::new(stream.bytes.as_slice()).deserialize::<i64>().unwrap_err();
+    match *err.kind() {
+        ErrorKind::Io(_) => assert!(err.source().is_some()),
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn error_is_send_sync() {
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+    assert_send_sync::<gob::Error>();
+}
@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+use std::sync::{Arc,Mutex};
+use gob::{Deserializer,ErrorKind};
+use gob::schema::Kind;
+use serde::de::IgnoredAny;
+use utils::wire::{self,Stream};
+
+fn stream() -> Stream {
+    let mut point = Vec::new();
+    wire::uint(&mut point, 1);
+    wire::int(&mut point, 1);
+    wire::uint(&mut point, 0);
+
+    Stream::new()
+        .structure(65, "main.Point", &[("X", 2)])
+        .value(65, &point)
+        .slice(66, "[]main.Secret", 6)
+        .value(66, &[0, 1, 2, b'h', b'i'])
+}
+
+#[test]
+fn called_for_each_definition() {
+    let stream = stream();
+    let defined = Arc::new(Mutex::new(Vec::new()));
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+
+    let log = defined.clone();
+    de.on_define(move |type_id, schema| {
+        log.lock().unwrap().push((type_id, schema.name().to_owned(), schema.kind()));
+        true
+    });
+
+    let point: HashMap<String, i64> = de.deserialize().unwrap();
+    assert_eq!(point["X"], 1);
+    assert_eq!(*defined.lock().unwrap(), vec![(65, "main.Point".to_owned(), Kind::Struct)]);
+
+    let secret: Vec<String> = de.deserialize().unwrap();
+    assert_eq!(secret, vec!["hi"]);
+    assert_eq!(defined.lock().unwrap().len(), 2);
+    assert_eq!(defined.lock().unwrap()[1], (66, "[]main.Secret".to_owned(), Kind::Slice));
+}
+
+#[test]
+fn veto() {
+    let stream = stream();
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+    de.on_define(|_, schema| !schema.name().contains("Secret"));
+
+    de.deserialize::<IgnoredAny>().unwrap();
+
+    let err = de.deserialize::<Vec<String>>().unwrap_err();
+    match *err.kind() {
+        ErrorKind::TypeRejected { type_id, ref type_name } => {
+            assert_eq!(type_id, 66);
+            assert_eq!(type_name, "[]main.Secret");
+        }
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+
+    // The rejected type is forgotten
+    assert_eq!(de.types().iter().map(|t| t.id()).collect::<Vec<_>>(), vec![65]);
+}
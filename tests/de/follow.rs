@@ -0,0 +1,144 @@
+extern crate tempdir;
+
+use std::fs::{self,File,OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use self::tempdir::TempDir;
+use gob::Follower;
+use utils::wire::{self,Stream};
+
+#[derive(Deserialize,Debug,PartialEq)]
+struct Event {
+    #[serde(rename="Id")] id: u64,
+}
+
+fn event(id: u64) -> Vec<u8> {
+    let mut event = Vec::new();
+    wire::uint(&mut event, 1);
+    wire::uint(&mut event, id);
+    wire::uint(&mut event, 0);
+    event
+}
+
+fn events(ids: &[u64]) -> Vec<u8> {
+    ids.iter()
+        .fold(Stream::new().structure(65, "Event", &[("Id", 3)]), |stream, &id| stream.value(65, &event(id)))
+        .bytes
+}
+
+fn append(path: &Path, bytes: &[u8]) {
+    OpenOptions::new().append(true).create(true).open(path).unwrap().write_all(bytes).unwrap();
+}
+
+fn next(follower: &mut Follower<Event>) -> Option<u64> {
+    follower.try_next().unwrap().map(|event| event.id)
+}
+
+#[test]
+fn partial_messages() {
+    let dir = TempDir::new("gob-follow").unwrap();
+    let path = dir.path().join("events.gob");
+    let bytes = events(&[1, 2]);
+    let split = bytes.len() - 2;
+
+    // Stop in the middle of the type definition, then in the middle of the second value
+    append(&path, &bytes[..5]);
+    let mut follower = Follower::open(&path).unwrap();
+    assert_eq!(next(&mut follower), None);
+
+    append(&path, &bytes[5..split]);
+    assert_eq!(next(&mut follower), Some(1));
+    assert_eq!(next(&mut follower), None);
+    assert_eq!(next(&mut follower), None);
+
+    append(&path, &bytes[split..]);
+    assert_eq!(next(&mut follower), Some(2));
+    assert_eq!(next(&mut follower), None);
+    assert_eq!(follower.offset(), bytes.len() as u64);
+}
+
+#[test]
+fn truncated_file() {
+    let dir = TempDir::new("gob-follow").unwrap();
+    let path = dir.path().join("events.gob");
+    append(&path, &events(&[1, 2]));
+
+    let mut follower = Follower::open(&path).unwrap();
+    assert_eq!(next(&mut follower), Some(1));
+    assert_eq!(next(&mut follower), Some(2));
+
+    File::create(&path).unwrap();
+    assert_eq!(next(&mut follower), None);
+
+    append(&path, &events(&[3]));
+    assert_eq!(next(&mut follower), Some(3));
+    assert_eq!(next(&mut follower), None);
+}
+
+#[cfg(unix)]
+#[test]
+fn truncated_and_rewritten_file() {
+    let dir = TempDir::new("gob-follow").unwrap();
+    let path = dir.path().join("events.gob");
+    append(&path, &events(&[1, 2]));
+
+    let mut follower = Follower::open(&path).unwrap();
+    assert_eq!(next(&mut follower), Some(1));
+    assert_eq!(next(&mut follower), Some(2));
+
+    // Written past the old offset before the follower looks again
+    File::create(&path).unwrap();
+    append(&path, &events(&[3, 4, 5, 6]));
+    assert_eq!(next(&mut follower), Some(3));
+    assert_eq!(next(&mut follower), Some(4));
+}
+
+#[cfg(unix)]
+#[test]
+fn rotated_file() {
+    let dir = TempDir::new("gob-follow").unwrap();
+    let path = dir.path().join("events.gob");
+    let bytes = events(&[1, 2, 3]);
+    let second = events(&[1]).len();
+    let third = events(&[1, 2]).len();
+    append(&path, &bytes[..second]);
+
+    let mut follower = Follower::open(&path).unwrap();
+    assert_eq!(next(&mut follower), Some(1));
+
+    // The old file gets another value just before it is rotated
+    append(&path, &bytes[second..third]);
+    fs::rename(&path, dir.path().join("events.gob.1")).unwrap();
+    assert_eq!(next(&mut follower), Some(2));
+
+    append(&path, &events(&[4]));
+    append(&dir.path().join("events.gob.1"), &bytes[third..]);
+    assert_eq!(next(&mut follower), Some(3));
+    assert_eq!(next(&mut follower), Some(4));
+    assert_eq!(next(&mut follower), None);
+}
+
+#[test]
+fn iteration_waits_for_values() {
+    let dir = TempDir::new("gob-follow").unwrap();
+    let path = dir.path().join("events.gob");
+    let bytes = events(&[1, 2]);
+    let split = bytes.len() - 1;
+    append(&path, &bytes[..split]);
+
+    let follower = Follower::<Event>::open(&path).unwrap().poll_interval(Duration::from_millis(1));
+
+    let writer = {
+        let path = path.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            append(&path, &bytes[split..]);
+        })
+    };
+
+    let ids = follower.take(2).map(|event| event.unwrap().id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![1, 2]);
+    writer.join().unwrap();
+}
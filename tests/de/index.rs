@@ -0,0 +1,94 @@
+use std::io::Cursor;
+use gob::{Corruption,Deserializer,ErrorKind,Index};
+use utils::wire::{Point,Stream,point};
+
+fn stream() -> Vec<u8> {
+    Stream::new()
+        .structure(65, "Point", &[("X", 2), ("Y", 2)])
+        .value(65, &point(1, 2))
+        .slice(66, "[]Point", 65)
+        .value(66, &[0, 1, 1, 2, 1, 2, 0])
+        .value(65, &point(5, 6))
+        .bytes
+}
+
+#[test]
+fn messages() {
+    let bytes = stream();
+    let index = Index::build(bytes.as_slice()).unwrap();
+
+    let kinds = index.messages().iter()
+        .map(|entry| (entry.is_definition(), entry.type_id()))
+        .collect::<Vec<_>>();
+    assert_eq!(kinds, vec![(true, 65), (false, 65), (true, 66), (false, 66), (false, 65)]);
+
+    let mut offset = 0;
+    for entry in index.messages() {
+        assert_eq!(entry.offset(), offset);
+        offset += entry.len();
+    }
+    assert_eq!(offset, bytes.len() as u64);
+
+    assert_eq!(index.len(), 3);
+    assert_eq!(index.type_map().len(), 2);
+    assert_eq!(index.value(1), Some(&index.messages()[3]));
+    assert_eq!(index.values().map(|entry| entry.type_id()).collect::<Vec<_>>(), vec![65, 66, 65]);
+}
+
+#[test]
+fn seek_values() {
+    let bytes = stream();
+    let index = Index::build(bytes.as_slice()).unwrap();
+    let mut de = Deserializer::new(Cursor::new(&bytes));
+
+    de.seek_value(&index, 2).unwrap();
+    assert_eq!(de.deserialize::<Point>().unwrap(), Point { x: 5, y: 6 });
+
+    de.seek_value(&index, 1).unwrap();
+    assert_eq!(de.deserialize::<Vec<Point>>().unwrap(), vec![Point { x: 1, y: 1 }]);
+    assert_eq!(de.deserialize::<Point>().unwrap(), Point { x: 5, y: 6 });
+
+    de.seek_value(&index, 0).unwrap();
+    assert_eq!(de.deserialize::<Point>().unwrap(), Point { x: 1, y: 2 });
+}
+
+#[test]
+fn seek_out_of_range() {
+    let bytes = stream();
+    let index = Index::build(bytes.as_slice()).unwrap();
+    let mut de = Deserializer::new(Cursor::new(&bytes));
+
+    match *de.seek_value(&index, 3).unwrap_err().kind() {
+        ErrorKind::Io(_) => {}
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn serialized_index() {
+    let bytes = stream();
+    let index = Index::build(bytes.as_slice()).unwrap();
+
+    let json = ::serde_json::to_value(&index).unwrap();
+    assert_eq!(json["messages"].as_array().unwrap().len(), 5);
+    assert!(json.get("values").is_none());
+
+    let index: Index = ::serde_json::from_value(json).unwrap();
+    assert_eq!(index.len(), 3);
+
+    let mut de = Deserializer::new(Cursor::new(&bytes));
+    de.seek_value(&index, 2).unwrap();
+    assert_eq!(de.deserialize::<Point>().unwrap(), Point { x: 5, y: 6 });
+}
+
+#[test]
+fn undefined_value_type() {
+    let bytes = Stream::new().value(65, &point(1, 2)).bytes;
+    let err = Index::build(bytes.as_slice()).unwrap_err();
+
+    match *err.kind() {
+        ErrorKind::Corrupt(Corruption::UndefinedType(65)) => {}
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+    assert_eq!(err.context().unwrap().message_index(), 0);
+}
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use gob::{Deserializer,DeserializerOptions,ErrorKind,Limit};
+use utils::wire::{self,Stream};
+
+fn ints(values: &[i64]) -> Vec<u8> {
+    let mut body = vec![0];
+    wire::uint(&mut body, values.len() as u64);
+    for &value in values {
+        wire::int(&mut body, value);
+    }
+    body
+}
+
+#[test]
+fn slice_and_map() {
+    let mut map = vec![0];
+    wire::uint(&mut map, 1);
+    wire::string(&mut map, "answer");
+    wire::int(&mut map, 42);
+
+    let stream = Stream::new()
+        .slice(65, "[]int", 2)
+        .value(65, &ints(&[1, 2, 3]))
+        .map(66, "map[string]int", 6, 2)
+        .value(66, &map);
+
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+    assert_eq!(de.deserialize::<Vec<i64>>().unwrap(), vec![1, 2, 3]);
+
+    let map = de.deserialize::<HashMap<String, i64>>().unwrap();
+    assert_eq!(map.len(), 1);
+    assert_eq!(map["answer"], 42);
+}
+
+#[test]
+fn message_size() {
+    let stream = Stream::new()
+        .slice(65, "[]int", 2)
+        .value(65, &ints(&[1, 2, 3]));
+
+    let options = DeserializerOptions::new().max_message_size(8);
+    let err = Deserializer::with_options(stream.bytes.as_slice(), options).deserialize::<Vec<i64>>().unwrap_err();
+    match *err.kind() {
+        ErrorKind::LimitExceeded(Limit::MessageSize(_)) => {},
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn bytes_len() {
+    // The string claims to be far longer than the message
+    let mut value = vec![0];
+    wire::uint(&mut value, 1 << 40);
+
+    let stream = Stream::new().value(6, &value);
+
+    let err = Deserializer::new(stream.bytes.as_slice()).deserialize::<String>().unwrap_err();
+    match *err.kind() {
+        ErrorKind::LimitExceeded(Limit::MessageSize(len)) => assert_eq!(len, 1 << 40),
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn elements() {
+    let stream = Stream::new()
+        .slice(65, "[]int", 2)
+        .value(65, &ints(&[1, 2, 3]));
+
+    let options = DeserializerOptions::new().max_elements(2);
+    let err = Deserializer::with_options(stream.bytes.as_slice(), options).deserialize::<Vec<i64>>().unwrap_err();
+    match *err.kind() {
+        ErrorKind::LimitExceeded(Limit::Elements(3)) => {},
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn depth() {
+    let mut value = vec![0, 1];
+    value.extend(&ints(&[1, 2])[1..]);
+
+    let stream = Stream::new()
+        .slice(65, "[]int", 2)
+        .slice(66, "[][]int", 65)
+        .value(66, &value);
+
+    let options = DeserializerOptions::new().max_depth(1);
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+    let err = de.deserialize_with_options::<Vec<Vec<i64>>>(options).unwrap_err();
+    match *err.kind() {
+        ErrorKind::LimitExceeded(Limit::Depth(1)) => {},
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn types() {
+    let stream = Stream::new()
+        .slice(65, "[]int", 2)
+        .slice(66, "[]string", 6)
+        .value(65, &ints(&[]));
+
+    let options = DeserializerOptions::new().max_types(1);
+    let err = Deserializer::with_options(stream.bytes.as_slice(), options).deserialize::<Vec<i64>>().unwrap_err();
+    match *err.kind() {
+        ErrorKind::LimitExceeded(Limit::Types(1)) => {},
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn fields() {
+    let stream = Stream::new()
+        .structure(65, "Point", &[("X", 2), ("Y", 2)])
+        .value(65, &[0]);
+
+    let options = DeserializerOptions::new().max_fields(1);
+    let err = Deserializer::with_options(stream.bytes.as_slice(), options).deserialize::<HashMap<String, i64>>().unwrap_err();
+    match *err.kind() {
+        ErrorKind::LimitExceeded(Limit::Fields(2)) => {},
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn fields_before_decoding_them() {
+    // A struct type claiming more fields than its definition holds
+    let mut body = Vec::new();
+    wire::int(&mut body, -65);
+    wire::uint(&mut body, 3);
+    wire::uint(&mut body, 1);
+    wire::uint(&mut body, 1);
+    wire::string(&mut body, "Point");
+    wire::uint(&mut body, 1);
+    wire::int(&mut body, 65);
+    wire::uint(&mut body, 0);
+    wire::uint(&mut body, 1);
+    wire::uint(&mut body, 1 << 16);
+    let mut stream = Vec::new();
+    wire::message(&mut stream, &body);
+
+    let options = DeserializerOptions::new().max_fields(1);
+    let err = Deserializer::with_options(stream.as_slice(), options).deserialize::<HashMap<String, i64>>().unwrap_err();
+    match *err.kind() {
+        ErrorKind::LimitExceeded(Limit::Fields(65536)) => {},
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
@@ -0,0 +1,40 @@
+extern crate tempdir;
+
+use std::fs::File;
+use std::io::Write;
+use memmap2::Mmap;
+use self::tempdir::TempDir;
+use gob::{Deserializer,Index};
+use utils::wire::{self,Stream};
+
+#[derive(Deserialize,Debug,PartialEq)]
+struct Entry<'a> {
+    #[serde(rename="Key")] key: &'a str,
+}
+
+#[test]
+fn borrow_from_mapping() {
+    let mut stream = Stream::new().structure(65, "Entry", &[("Key", 6)]);
+    for key in &["a", "bb", "ccc"] {
+        let mut entry = Vec::new();
+        wire::uint(&mut entry, 1);
+        wire::string(&mut entry, key);
+        wire::uint(&mut entry, 0);
+        stream = stream.value(65, &entry);
+    }
+
+    let dir = TempDir::new("gob-mmap").unwrap();
+    let path = dir.path().join("entries.gob");
+    File::create(&path).unwrap().write_all(&stream.bytes).unwrap();
+
+    let file = File::open(&path).unwrap();
+    let mmap = unsafe { Mmap::map(&file).unwrap() };
+
+    let index = Index::build(&mmap[..]).unwrap();
+    let mut de = Deserializer::from_mmap(&mmap);
+    de.seek_value(&index, 2).unwrap();
+
+    let entry: Entry = de.deserialize().unwrap();
+    assert_eq!(entry.key, "ccc");
+    assert!(mmap.as_ptr_range().contains(&entry.key.as_ptr()));
+}
@@ -1 +1,27 @@
+#[cfg(feature = "tokio")]
+mod async_read;
+mod borrow;
 mod builtins;
+mod checkpoint;
+#[cfg(feature = "codec")]
+mod codec;
+mod context;
+mod define_hook;
+mod follow;
+mod index;
+mod limits;
+#[cfg(feature = "memmap")]
+mod mmap;
+mod parallel;
+mod peek;
+mod preload;
+mod push;
+mod projection;
+mod raw;
+mod reader;
+mod router;
+mod schema;
+mod shared_types;
+mod stream;
+mod validation;
+mod value;
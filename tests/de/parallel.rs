@@ -0,0 +1,130 @@
+use gob::{Corruption,Deserializer,ErrorKind};
+use serde::de::Deserialize;
+use utils::wire::{self,Stream};
+
+#[derive(Deserialize,Debug,PartialEq)]
+struct Record {
+    #[serde(rename="Id")] id: u64,
+    #[serde(rename="Tags")] tags: Vec<String>,
+}
+
+fn record(id: u64) -> Vec<u8> {
+    let mut record = Vec::new();
+    wire::uint(&mut record, 1);
+    wire::uint(&mut record, id);
+    wire::uint(&mut record, 1);
+    wire::uint(&mut record, id % 3);
+    for _ in 0..id % 3 {
+        wire::string(&mut record, &id.to_string());
+    }
+    wire::uint(&mut record, 0);
+    record
+}
+
+/// Records, with a type defined halfway that later records use
+fn records(count: u64) -> Stream {
+    let mut stream = Stream::new()
+        .slice(65, "[]string", 6)
+        .structure(66, "Record", &[("Id", 3), ("Tags", 65)]);
+
+    for id in 0..count {
+        if id == count / 2 {
+            stream = stream.structure(67, "Tagged", &[("Id", 3), ("Tags", 65)]);
+        }
+        stream = stream.value(if id < count / 2 { 66 } else { 67 }, &record(id));
+    }
+
+    stream
+}
+
+fn expected(count: u64) -> Vec<Record> {
+    (0..count)
+        .map(|id| Record { id, tags: (0..id % 3).map(|_| id.to_string()).collect() })
+        .collect()
+}
+
+#[test]
+fn values_in_stream_order() {
+    let stream = records(1000);
+
+    for &threads in &[1, 2, 8] {
+        let records = Deserializer::new(stream.bytes.as_slice())
+            .into_parallel_iter::<Record>(threads)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(records, expected(1000));
+    }
+}
+
+#[test]
+fn empty_stream() {
+    let mut values = Deserializer::new(&[][..]).into_parallel_iter::<Record>(4);
+    assert!(values.next().is_none());
+    assert!(values.next().is_none());
+}
+
+#[test]
+fn decode_error_in_order() {
+    let mut bad = record(2);
+    bad[1] = 0xf0; // Id is out of range
+
+    let before = Stream::new()
+        .slice(65, "[]string", 6)
+        .structure(66, "Record", &[("Id", 3), ("Tags", 65)])
+        .value(66, &record(0))
+        .value(66, &record(1));
+    let start = before.bytes.len() as u64;
+    let stream = before.value(66, &bad);
+    let end = stream.bytes.len() as u64;
+    let stream = stream.value(66, &record(3));
+
+    let mut values = Deserializer::new(stream.bytes.as_slice()).into_parallel_iter::<Record>(4);
+    assert_eq!(values.next().unwrap().unwrap().id, 0);
+    assert_eq!(values.next().unwrap().unwrap().id, 1);
+
+    let err = values.next().unwrap().unwrap_err();
+    match *err.kind() {
+        ErrorKind::Corrupt(Corruption::NumOutOfRange) => {}
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+    let context = err.context().unwrap();
+    assert_eq!(context.message_index(), 4);
+    // Counted from the start of the stream, not of the batch
+    assert!(context.offset() > start && context.offset() < end, "{} not in {}..{}", context.offset(), start, end);
+
+    assert!(values.next().is_none());
+}
+
+#[test]
+fn scan_error_after_preceding_values() {
+    let stream = records(10).value(70, &record(10));
+
+    let values = Deserializer::new(stream.bytes.as_slice())
+        .into_parallel_iter::<Record>(4)
+        .collect::<Vec<_>>();
+
+    assert_eq!(values.len(), 11);
+    assert!(values[..10].iter().all(Result::is_ok));
+
+    match *values[10].as_ref().unwrap_err().kind() {
+        ErrorKind::Corrupt(Corruption::UndefinedType(70)) => {}
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+struct Panicking;
+
+impl<'de> Deserialize<'de> for Panicking {
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_ignored_any(::serde::de::IgnoredAny)?;
+        panic!("decoding failed")
+    }
+}
+
+#[test]
+#[should_panic(expected = "decoding failed")]
+fn panics_are_resumed() {
+    let stream = records(4);
+    Deserializer::new(stream.bytes.as_slice()).into_parallel_iter::<Panicking>(2).next();
+}
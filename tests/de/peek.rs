@@ -0,0 +1,30 @@
+use gob::Deserializer;
+use utils::wire::{Point,Stream,point};
+
+#[test]
+fn heterogeneous_stream() {
+    let stream = Stream::new()
+        .structure(65, "main.Point", &[("X", 2), ("Y", 2)])
+        .value(65, &point(1, 2))
+        .slice(66, "[]string", 6)
+        .value(66, &[0, 1, 2, b'h', b'i']);
+
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+
+    let schema = de.peek_type().unwrap().unwrap();
+    assert_eq!(schema.id(), 65);
+    assert_eq!(schema.name(), "main.Point");
+    let fields = schema.fields().iter().map(|f| (f.name(), f.type_id())).collect::<Vec<_>>();
+    assert_eq!(fields, vec![("X", 2), ("Y", 2)]);
+
+    // Peeking again doesn't consume anything
+    assert_eq!(de.peek_type().unwrap().unwrap(), schema);
+    assert_eq!(de.deserialize::<Point>().unwrap(), Point { x: 1, y: 2 });
+
+    let schema = de.peek_type().unwrap().unwrap();
+    assert_eq!(schema.name(), "[]string");
+    assert!(schema.fields().is_empty());
+    assert_eq!(de.deserialize::<Vec<String>>().unwrap(), vec!["hi".to_string()]);
+
+    assert!(de.peek_type().unwrap().is_none());
+}
@@ -0,0 +1,87 @@
+use gob::{Deserializer,DeserializerOptions,ErrorKind,Limit,TypeMap};
+use utils::wire::{self,Point,Stream,point};
+
+fn schema() -> Stream {
+    Stream::new()
+        .structure(65, "Point", &[("X", 2), ("Y", 2)])
+        .slice(66, "[]Point", 65)
+}
+
+fn decode_values(types: TypeMap) -> Vec<Point> {
+    let mut values = Vec::new();
+    wire::uint(&mut values, 0);
+    wire::uint(&mut values, 1);
+    values.extend(point(3, 4));
+
+    let stream = Stream::new()
+        .value(65, &point(1, 2))
+        .value(66, &values);
+
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+    de.set_type_map(types);
+
+    let mut points = vec![de.deserialize::<Point>().unwrap()];
+    points.extend(de.deserialize::<Vec<Point>>().unwrap());
+    points
+}
+
+#[test]
+fn definitions_only_stream() {
+    let schema = schema();
+    let types = TypeMap::from_reader(schema.bytes.as_slice()).unwrap();
+
+    assert_eq!(types.len(), 2);
+    assert_eq!(decode_values(types), vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]);
+}
+
+#[test]
+fn values_are_skipped() {
+    let schema = Stream::new()
+        .structure(65, "Point", &[("X", 2), ("Y", 2)])
+        .value(65, &point(9, 9))
+        .slice(66, "[]Point", 65)
+        .value(66, &[0, 0]);
+
+    let types = TypeMap::from_reader(schema.bytes.as_slice()).unwrap();
+    assert_eq!(decode_values(types).len(), 2);
+}
+
+#[test]
+fn serde_round_trip() {
+    let schema = schema();
+    let types = TypeMap::from_reader(schema.bytes.as_slice()).unwrap();
+
+    let json = ::serde_json::to_value(&types).unwrap();
+    assert_eq!(json[0]["StructT"]["CommonType"]["Name"], "Point");
+    assert_eq!(json[0]["StructT"]["Field"][1]["Name"], "Y");
+    assert_eq!(json[1]["SliceT"]["Elem"], 65);
+
+    let types: TypeMap = ::serde_json::from_value(json).unwrap();
+    assert_eq!(decode_values(types).len(), 2);
+}
+
+#[test]
+fn serde_dangling_reference() {
+    let json = r#"[{"SliceT": {"CommonType": {"Name": "[]Point", "Id": 66}, "Elem": 65}}]"#;
+    let err = ::serde_json::from_str::<TypeMap>(json).unwrap_err();
+    assert!(err.to_string().contains("undefined type 65"), "{}", err);
+}
+
+#[test]
+fn limited_types() {
+    let schema = schema();
+    let options = DeserializerOptions::new().max_types(1);
+    let err = TypeMap::from_reader_with_options(schema.bytes.as_slice(), options).unwrap_err();
+    match *err.kind() {
+        ErrorKind::LimitExceeded(Limit::Types(1)) => {},
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn serde_limited_fields() {
+    let fields = (0..2000).map(|i| format!(r#"{{"Name": "F{}", "Id": 2}}"#, i)).collect::<Vec<_>>();
+    let json = format!(r#"[{{"StructT": {{"CommonType": {{"Name": "Wide", "Id": 65}}, "Field": [{}]}}}}]"#, fields.join(","));
+    let err = ::serde_json::from_str::<TypeMap>(&json).unwrap_err();
+    assert!(err.to_string().contains("2000 fields"), "{}", err);
+}
@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+use gob::{Deserializer,ErrorKind,Projection};
+use utils::wire::{self,Stream};
+
+#[derive(Deserialize,Debug,PartialEq)]
+struct Order {
+    #[serde(rename="Header")] header: Header,
+    #[serde(rename="Items")] items: Vec<Item>,
+}
+
+#[derive(Deserialize,Debug,PartialEq)]
+struct Header {
+    #[serde(rename="Timestamp")] timestamp: i64,
+}
+
+#[derive(Deserialize,Debug,PartialEq)]
+struct Item {
+    #[serde(rename="Sku")] sku: String,
+}
+
+fn item(out: &mut Vec<u8>, sku: &str, qty: i64) {
+    wire::uint(out, 1);
+    wire::string(out, sku);
+    wire::uint(out, 1);
+    wire::int(out, qty);
+    wire::uint(out, 1);
+    wire::string(out, "fragile");
+    wire::uint(out, 0);
+}
+
+/// type Order struct { Id uint; Header Header; Items []Item; Stock map[string]Item }
+fn orders() -> Stream {
+    let mut order = Vec::new();
+    wire::uint(&mut order, 1);
+    wire::uint(&mut order, 42);
+    wire::uint(&mut order, 1);
+    wire::uint(&mut order, 1);
+    wire::int(&mut order, 1500);
+    wire::uint(&mut order, 1);
+    wire::string(&mut order, "web");
+    wire::uint(&mut order, 0);
+    wire::uint(&mut order, 1);
+    wire::uint(&mut order, 2);
+    item(&mut order, "A-1", 3);
+    item(&mut order, "B-2", 5);
+    wire::uint(&mut order, 1);
+    wire::uint(&mut order, 1);
+    wire::string(&mut order, "north");
+    item(&mut order, "C-3", 7);
+    wire::uint(&mut order, 0);
+
+    Stream::new()
+        .structure(65, "Header", &[("Timestamp", 2), ("Source", 6)])
+        .structure(66, "Item", &[("Sku", 6), ("Qty", 2), ("Notes", 6)])
+        .slice(67, "[]Item", 66)
+        .map(68, "map[string]Item", 6, 66)
+        .structure(69, "Order", &[("Id", 3), ("Header", 65), ("Items", 67), ("Stock", 68)])
+        .value(69, &order)
+}
+
+#[test]
+fn selected_fields() {
+    let stream = orders();
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+    let projection = Projection::new(["Header.Timestamp", "Items[].Sku"]).unwrap();
+
+    let order: Order = de.deserialize_projected(&projection).unwrap();
+    assert_eq!(order, Order {
+        header: Header { timestamp: 1500 },
+        items: vec![Item { sku: "A-1".into() }, Item { sku: "B-2".into() }],
+    });
+    assert!(de.peek_type().unwrap().is_none());
+}
+
+#[test]
+fn map_values() {
+    #[derive(Deserialize,Debug,PartialEq)]
+    struct Stock {
+        #[serde(rename="Stock")] stock: BTreeMap<String, Qty>,
+    }
+
+    #[derive(Deserialize,Debug,PartialEq)]
+    struct Qty {
+        #[serde(rename="Qty")] qty: i64,
+    }
+
+    let stream = orders();
+    let projection = Projection::new(["Stock[].Qty"]).unwrap();
+    let stock: Stock = Deserializer::new(stream.bytes.as_slice()).deserialize_projected(&projection).unwrap();
+
+    assert_eq!(stock.stock.into_iter().collect::<Vec<_>>(), vec![("north".to_string(), Qty { qty: 7 })]);
+}
+
+#[test]
+fn whole_value_supersedes_parts() {
+    #[derive(Deserialize,Debug,PartialEq)]
+    struct Full {
+        #[serde(rename="Header")] header: FullHeader,
+    }
+
+    #[derive(Deserialize,Debug,PartialEq)]
+    struct FullHeader {
+        #[serde(rename="Timestamp")] timestamp: i64,
+        #[serde(rename="Source")] source: String,
+    }
+
+    let stream = orders();
+    let projection = Projection::new(["Header.Timestamp", "Header"]).unwrap();
+    let full: Full = Deserializer::new(stream.bytes.as_slice()).deserialize_projected(&projection).unwrap();
+
+    assert_eq!(full.header, FullHeader { timestamp: 1500, source: "web".into() });
+}
+
+#[test]
+fn invalid_paths() {
+    let stream = orders();
+
+    for paths in &[&["Header.Date"][..], &["Header[]"][..], &["Id.Value"][..], &["[]"][..]] {
+        let projection = Projection::new(*paths).unwrap();
+        let err = Deserializer::new(stream.bytes.as_slice()).deserialize_projected::<Order>(&projection).unwrap_err();
+
+        match *err.kind() {
+            ErrorKind::InvalidProjection(_) => {},
+            ref kind => panic!("unexpected error for {:?}: {:?}", paths, kind),
+        }
+    }
+
+    for path in &["", "Items.", ".Items", "Items[", "Items[0]", "Items.[]", "It]ems"] {
+        match *Projection::new([path]).unwrap_err().kind() {
+            ErrorKind::InvalidProjection(_) => {},
+            ref kind => panic!("unexpected error for {:?}: {:?}", path, kind),
+        }
+    }
+
+    assert!(Projection::new(Vec::<String>::new()).is_err());
+}
+
+#[test]
+fn reused_across_streams() {
+    let projection = Projection::new(["Timestamp"]).unwrap();
+
+    let mut header = Vec::new();
+    wire::uint(&mut header, 1);
+    wire::int(&mut header, 1500);
+    wire::uint(&mut header, 1);
+    wire::string(&mut header, "web");
+    wire::uint(&mut header, 0);
+
+    let stream = Stream::new()
+        .structure(65, "Header", &[("Timestamp", 2), ("Source", 6)])
+        .value(65, &header)
+        .value(65, &header);
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+
+    for _ in 0..2 {
+        assert_eq!(de.deserialize_projected::<Header>(&projection).unwrap(), Header { timestamp: 1500 });
+    }
+
+    // The same type id, defined with the fields the other way around
+    let mut header = Vec::new();
+    wire::uint(&mut header, 1);
+    wire::string(&mut header, "web");
+    wire::uint(&mut header, 1);
+    wire::int(&mut header, 1600);
+    wire::uint(&mut header, 0);
+
+    let stream = Stream::new()
+        .structure(65, "Header", &[("Source", 6), ("Timestamp", 2)])
+        .value(65, &header);
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+
+    assert_eq!(de.deserialize_projected::<Header>(&projection).unwrap(), Header { timestamp: 1600 });
+}
@@ -0,0 +1,147 @@
+use std::io;
+use gob::{Corruption,DeserializerOptions,ErrorKind,Limit,Message,PushDecoder};
+use utils::wire::{Point,Stream,point,points};
+
+fn poll_all(decoder: &mut PushDecoder<Point>) -> Vec<Message<Point>> {
+    let mut messages = Vec::new();
+    while let Some(message) = decoder.poll_message().unwrap() {
+        messages.push(message);
+    }
+    messages
+}
+
+fn check_points(messages: &[Message<Point>]) {
+    assert_eq!(messages.len(), 3);
+
+    match messages[0] {
+        Message::Definition(ref schema) => {
+            assert_eq!(schema.id(), 65);
+            assert_eq!(schema.name(), "Point");
+        }
+        ref message => panic!("unexpected message: {:?}", message),
+    }
+
+    assert_eq!(messages[1], Message::Value(Point { x: 1, y: 2 }));
+    assert_eq!(messages[2], Message::Value(Point { x: -3, y: 4 }));
+}
+
+#[test]
+fn definitions_before_values() {
+    let mut decoder = PushDecoder::new();
+    decoder.feed(&points().bytes);
+
+    check_points(&poll_all(&mut decoder));
+    assert_eq!(decoder.buffered(), 0);
+    assert_eq!(decoder.offset(), points().bytes.len() as u64);
+}
+
+#[test]
+fn fed_a_byte_at_a_time() {
+    let mut decoder = PushDecoder::new();
+    let mut messages = Vec::new();
+
+    for &byte in &points().bytes {
+        decoder.feed(&[byte]);
+        messages.extend(poll_all(&mut decoder));
+    }
+
+    check_points(&messages);
+}
+
+#[test]
+fn fed_in_uneven_chunks() {
+    // Chunks end in the middle of messages, leaving decoded bytes in front of pending ones
+    let mut stream = Stream::new().structure(65, "Point", &[("X", 2), ("Y", 2)]);
+    for i in 0..100 {
+        stream = stream.value(65, &point(i, -i));
+    }
+
+    let mut decoder = PushDecoder::new();
+    let mut values = Vec::new();
+
+    for chunk in stream.bytes.chunks(7) {
+        decoder.feed(chunk);
+        values.extend(poll_all(&mut decoder).into_iter().filter_map(|message| match message {
+            Message::Value(value) => Some(value),
+            Message::Definition(_) => None,
+        }));
+    }
+
+    assert_eq!(values, (0..100).map(|i| Point { x: i, y: -i }).collect::<Vec<_>>());
+    assert_eq!(decoder.buffered(), 0);
+    assert_eq!(decoder.offset(), stream.bytes.len() as u64);
+}
+
+#[test]
+fn trailing_definitions() {
+    let mut decoder = PushDecoder::<Vec<i64>>::new();
+    decoder.feed(&Stream::new().slice(65, "[]int", 2).bytes);
+
+    // The definition could still be followed by more definitions
+    assert_eq!(decoder.poll_message().unwrap(), None);
+
+    decoder.finish();
+    match decoder.poll_message().unwrap() {
+        Some(Message::Definition(ref schema)) => assert_eq!(schema.name(), "[]int"),
+        message => panic!("unexpected message: {:?}", message),
+    }
+    assert_eq!(decoder.poll_message().unwrap(), None);
+}
+
+#[test]
+fn partial_message_at_end() {
+    let bytes = points().bytes;
+    let mut decoder = PushDecoder::<Point>::new();
+    decoder.feed(&bytes[..bytes.len() - 1]);
+    assert_eq!(poll_all(&mut decoder).len(), 2);
+
+    decoder.finish();
+    let err = decoder.poll_message().unwrap_err();
+    match *err.kind() {
+        ErrorKind::Io(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => {},
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+    assert_eq!(decoder.poll_message().unwrap(), None);
+}
+
+#[test]
+fn skips_failed_value() {
+    let stream = points()
+        .value(66, &point(5, 6))
+        .value(65, &point(7, 8));
+
+    let mut decoder = PushDecoder::new();
+    decoder.feed(&stream.bytes);
+    check_points(&[
+        decoder.poll_message().unwrap().unwrap(),
+        decoder.poll_message().unwrap().unwrap(),
+        decoder.poll_message().unwrap().unwrap(),
+    ]);
+
+    let err = decoder.poll_message().unwrap_err();
+    match *err.kind() {
+        ErrorKind::Corrupt(Corruption::UndefinedType(66)) => {},
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+
+    assert_eq!(decoder.poll_message().unwrap(), Some(Message::Value(Point { x: 7, y: 8 })));
+    assert_eq!(decoder.poll_message().unwrap(), None);
+}
+
+#[test]
+fn framing_error_is_terminal() {
+    // A message claiming to be as long as possible
+    let options = DeserializerOptions::new().max_message_size(usize::MAX);
+    let mut decoder = PushDecoder::<Point>::with_options(options);
+    decoder.feed(&[0xf8, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+
+    for _ in 0..2 {
+        let err = decoder.poll_message().unwrap_err();
+        match *err.kind() {
+            ErrorKind::LimitExceeded(Limit::MessageSize(_)) => {},
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+
+        decoder.feed(&points().bytes);
+    }
+}
@@ -0,0 +1,89 @@
+use gob::{Deserializer,RawValue};
+use utils::wire::{self,Point,Stream,point};
+
+#[derive(Deserialize,Debug)]
+struct Envelope {
+    #[serde(rename="Topic")] topic: String,
+    #[serde(rename="Points")] points: RawValue,
+}
+
+fn envelope_stream() -> Stream {
+    let mut envelope = Vec::new();
+    wire::uint(&mut envelope, 1);
+    wire::string(&mut envelope, "shapes");
+    wire::uint(&mut envelope, 1);
+    wire::uint(&mut envelope, 2);
+    envelope.extend(point(1, 2));
+    envelope.extend(point(3, 4));
+    wire::uint(&mut envelope, 0);
+
+    Stream::new()
+        .structure(65, "Point", &[("X", 2), ("Y", 2)])
+        .slice(66, "[]Point", 65)
+        .structure(67, "Envelope", &[("Topic", 6), ("Points", 66)])
+        .value(67, &envelope)
+}
+
+#[test]
+fn top_level() {
+    let value = point(1, 2);
+
+    let stream = Stream::new()
+        .structure(65, "Point", &[("X", 2), ("Y", 2)])
+        .value(65, &value)
+        .value(65, &value);
+
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+    let raw: RawValue = de.deserialize().unwrap();
+    assert_eq!(raw.schema().unwrap().name(), "Point");
+    assert_eq!(raw.decode::<Point>().unwrap(), Point { x: 1, y: 2 });
+
+    // The definition is repeated for the capture of the second value
+    let again: RawValue = de.deserialize().unwrap();
+    assert_eq!(again, raw);
+    assert!(de.peek_type().unwrap().is_none());
+}
+
+#[test]
+fn struct_field() {
+    let stream = envelope_stream();
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+
+    let envelope: Envelope = de.deserialize().unwrap();
+    assert_eq!(envelope.topic, "shapes");
+    assert_eq!(envelope.points.schema().unwrap().name(), "[]Point");
+    assert_eq!(envelope.points.decode::<Vec<Point>>().unwrap(), vec![
+        Point { x: 1, y: 2 },
+        Point { x: 3, y: 4 },
+    ]);
+}
+
+#[test]
+fn self_contained() {
+    let stream = envelope_stream();
+    let points = Deserializer::new(stream.bytes.as_slice()).deserialize::<Envelope>().unwrap().points;
+    drop(stream);
+
+    // Only the types the value needs are carried along
+    let mut de = Deserializer::new(points.as_bytes());
+    assert_eq!(de.peek_type().unwrap().unwrap().id(), 66);
+    assert_eq!(de.deserialize::<Vec<Point>>().unwrap().len(), 2);
+    assert!(de.peek_type().unwrap().is_none());
+}
+
+#[test]
+fn elements() {
+    let mut value = Vec::new();
+    wire::uint(&mut value, 0);
+    wire::uint(&mut value, 2);
+    wire::string(&mut value, "a");
+    wire::string(&mut value, "bc");
+
+    let stream = Stream::new()
+        .slice(65, "[]string", 6)
+        .value(65, &value);
+
+    let values: Vec<RawValue> = Deserializer::new(stream.bytes.as_slice()).deserialize().unwrap();
+    let values = values.iter().map(|value| value.decode::<String>().unwrap()).collect::<Vec<_>>();
+    assert_eq!(values, vec!["a", "bc"]);
+}
@@ -0,0 +1,74 @@
+use std::io::{self,Read};
+use gob::{Deserializer,RawValue};
+use utils::wire::{self,Stream};
+
+/// Hands out a single byte per read, like a slow network connection
+struct Trickle<R>(R);
+
+impl<R: Read> Read for Trickle<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(1);
+        self.0.read(&mut buf[..len])
+    }
+}
+
+#[derive(Deserialize,Debug,PartialEq)]
+struct Record {
+    #[serde(rename="Id")] id: u64,
+    #[serde(rename="Body")] body: String,
+}
+
+fn records(body: &str) -> Stream {
+    let mut stream = Stream::new().structure(65, "Record", &[("Id", 3), ("Body", 6)]);
+
+    for id in &[1, 300, 1 << 40] {
+        let mut record = Vec::new();
+        wire::uint(&mut record, 1);
+        wire::uint(&mut record, *id);
+        wire::uint(&mut record, 1);
+        wire::string(&mut record, body);
+        wire::uint(&mut record, 0);
+        stream = stream.value(65, &record);
+    }
+
+    stream
+}
+
+fn decode_all<R: Read>(reader: R) -> Vec<Record> {
+    Deserializer::new(reader).into_iter().collect::<Result<_, _>>().unwrap()
+}
+
+#[test]
+fn partial_reads() {
+    let stream = records("hello");
+    let records = decode_all(Trickle(stream.bytes.as_slice()));
+
+    assert_eq!(records.iter().map(|r| r.id).collect::<Vec<_>>(), vec![1, 300, 1 << 40]);
+    assert!(records.iter().all(|r| r.body == "hello"));
+}
+
+#[test]
+fn values_larger_than_buffer() {
+    let body = "x".repeat(100_000);
+    let stream = records(&body);
+
+    for records in [decode_all(stream.bytes.as_slice()), decode_all(Trickle(stream.bytes.as_slice()))] {
+        assert_eq!(records.len(), 3);
+        assert!(records.iter().all(|r| r.body == body));
+    }
+}
+
+#[test]
+fn offsets_count_consumed_bytes() {
+    let stream = records("hello");
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+
+    de.deserialize::<Record>().unwrap();
+    let first = de.offset();
+    let raw: RawValue = de.deserialize().unwrap();
+    de.deserialize::<Record>().unwrap();
+
+    assert!(first < stream.bytes.len() as u64);
+    assert_eq!(raw.decode::<Record>().unwrap().id, 300);
+    assert_eq!(de.offset(), stream.bytes.len() as u64);
+}
@@ -0,0 +1,82 @@
+use std::cell::RefCell;
+use gob::{Deserializer,Router};
+use utils::wire::{self,Stream};
+
+#[derive(Deserialize,Debug,PartialEq)]
+struct Heartbeat {
+    #[serde(rename="Seq")] seq: u64,
+}
+
+#[derive(Deserialize,Debug,PartialEq)]
+struct Login {
+    #[serde(rename="User")] user: String,
+}
+
+#[derive(Deserialize,Debug,PartialEq)]
+struct Logout {
+    #[serde(rename="Session")] session: i64,
+}
+
+fn stream() -> Stream {
+    let mut heartbeat = Vec::new();
+    wire::uint(&mut heartbeat, 1);
+    wire::uint(&mut heartbeat, 7);
+    wire::uint(&mut heartbeat, 0);
+
+    let mut login = Vec::new();
+    wire::uint(&mut login, 1);
+    wire::string(&mut login, "gopher");
+    wire::uint(&mut login, 0);
+
+    let mut logout = Vec::new();
+    wire::uint(&mut logout, 1);
+    wire::int(&mut logout, 3);
+    wire::uint(&mut logout, 0);
+
+    Stream::new()
+        .structure(65, "Heartbeat", &[("Seq", 3)])
+        .value(65, &heartbeat)
+        .structure(66, "Login", &[("User", 6)])
+        .value(66, &login)
+        .structure(67, "Logout", &[("Session", 2)])
+        .value(67, &logout)
+        .value(65, &heartbeat)
+}
+
+#[test]
+fn dispatch_by_type_name() {
+    let stream = stream();
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+    let events = RefCell::new(Vec::new());
+
+    Router::new()
+        .on("Heartbeat", |hb: Heartbeat| events.borrow_mut().push(format!("heartbeat {}", hb.seq)))
+        .on("Login", |login: Login| events.borrow_mut().push(format!("login {}", login.user)))
+        .fallback(|schema, value| {
+            let session: Logout = value.decode().unwrap();
+            events.borrow_mut().push(format!("{} {}", schema.name(), session.session))
+        })
+        .run(&mut de)
+        .unwrap();
+
+    assert_eq!(events.into_inner(), vec![
+        "heartbeat 7",
+        "login gopher",
+        "Logout 3",
+        "heartbeat 7",
+    ]);
+}
+
+#[test]
+fn skip_unhandled() {
+    let stream = stream();
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+    let mut logins = Vec::new();
+
+    Router::new()
+        .on("Login", |login: Login| logins.push(login))
+        .run(&mut de)
+        .unwrap();
+
+    assert_eq!(logins, vec![Login { user: "gopher".into() }]);
+}
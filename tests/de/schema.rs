@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use gob::Deserializer;
+use gob::schema::Kind;
+use utils::wire::{self,Stream};
+
+fn stream() -> Stream {
+    let mut value = vec![0];
+    wire::uint(&mut value, 0);
+
+    Stream::new()
+        .structure(65, "Point", &[("X", 2), ("Y", 2)])
+        .slice(66, "[]Point", 65)
+        .array(67, "[3]float64", 4, 3)
+        .map(68, "map[string][]Point", 6, 66)
+        .value(68, &value)
+}
+
+#[test]
+fn types() {
+    let stream = stream();
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+    assert!(de.types().is_empty());
+
+    de.peek_type().unwrap();
+    let types = de.types();
+    assert_eq!(types.iter().map(|t| t.id()).collect::<Vec<_>>(), vec![65, 66, 67, 68]);
+
+    let point = &types[0];
+    assert_eq!(point.name(), "Point");
+    assert_eq!(point.kind(), Kind::Struct);
+    let fields = point.fields().iter().map(|f| (f.name(), f.type_id())).collect::<Vec<_>>();
+    assert_eq!(fields, vec![("X", 2), ("Y", 2)]);
+    assert_eq!(point.elem(), None);
+
+    let points = &types[1];
+    assert_eq!(points.kind(), Kind::Slice);
+    assert_eq!(points.elem(), Some(65));
+    assert_eq!(points.len(), None);
+    assert!(points.fields().is_empty());
+
+    let array = &types[2];
+    assert_eq!(array.name(), "[3]float64");
+    assert_eq!(array.kind(), Kind::Array);
+    assert_eq!(array.elem(), Some(4));
+    assert_eq!(array.len(), Some(3));
+
+    let map = &types[3];
+    assert_eq!(map.kind(), Kind::Map);
+    assert_eq!(map.key(), Some(6));
+    assert_eq!(map.elem(), Some(66));
+}
+
+#[test]
+fn builtin_types() {
+    let stream = stream();
+    let de = Deserializer::new(stream.bytes.as_slice());
+    let types = de.type_map();
+
+    let string = types.schema(6).unwrap();
+    assert_eq!(string.name(), "string");
+    assert_eq!(string.kind(), Kind::String);
+    assert_eq!(types.schema(5).unwrap().kind(), Kind::Bytes);
+    assert_eq!(types.schema(8).unwrap().kind(), Kind::Interface);
+
+    // Custom types are unknown until their definitions have been read
+    assert_eq!(types.schema(65), None);
+    assert_eq!(types.schema(1000), None);
+}
+
+#[test]
+fn types_of_type_map() {
+    let stream = stream();
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+    let _: HashMap<String, Vec<()>> = de.deserialize().unwrap();
+
+    let types = de.type_map();
+    assert_eq!(types.types(), de.types());
+    assert_eq!(types.schema(67), Some(de.types()[2].clone()));
+}
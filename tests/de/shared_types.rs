@@ -0,0 +1,61 @@
+use std::thread;
+use gob::{Deserializer,TypeMap};
+use utils::wire::{Point,Stream,point};
+
+#[test]
+fn send_sync() {
+    fn assert_send<T: Send>() {}
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send::<Deserializer<&[u8]>>();
+    assert_send_sync::<TypeMap>();
+}
+
+#[test]
+fn decode_on_worker_threads() {
+    let stream = Stream::new()
+        .structure(65, "Point", &[("X", 2), ("Y", 2)])
+        .value(65, &point(0, 0));
+
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+    de.deserialize::<Point>().unwrap();
+    let types = de.type_map();
+    assert_eq!(types.len(), 1);
+
+    let workers = (1..5).map(|i| {
+        let types = types.clone();
+        thread::spawn(move || {
+            // Value-only messages, whose type was defined on the main thread
+            let values = Stream::new().value(65, &point(i, -i));
+            let mut de = Deserializer::new(values.bytes.as_slice());
+            de.set_type_map(types);
+            de.deserialize::<Point>().unwrap()
+        })
+    }).collect::<Vec<_>>();
+
+    let points = workers.into_iter().map(|worker| worker.join().unwrap()).collect::<Vec<_>>();
+    assert_eq!(points, (1..5).map(|i| Point { x: i, y: -i }).collect::<Vec<_>>());
+}
+
+#[test]
+fn snapshots_are_immutable() {
+    let stream = Stream::new()
+        .structure(65, "Point", &[("X", 2), ("Y", 2)])
+        .value(65, &point(1, 2))
+        .slice(66, "[]Point", 65)
+        .value(66, &[0, 0]);
+
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+    de.deserialize::<Point>().unwrap();
+    let snapshot = de.type_map();
+
+    de.deserialize::<Vec<Point>>().unwrap();
+    assert_eq!(de.type_map().len(), 2);
+    assert_eq!(snapshot.len(), 1);
+
+    // The snapshot doesn't know about the slice type
+    let values = Stream::new().value(66, &[0, 0]);
+    let mut de = Deserializer::new(values.bytes.as_slice());
+    de.set_type_map(snapshot);
+    assert!(de.deserialize::<Vec<Point>>().is_err());
+}
@@ -0,0 +1,53 @@
+use gob::{Deserializer,ErrorKind};
+use utils::wire::{Point,Stream,point,points};
+
+fn three_points() -> Stream {
+    points().value(65, &point(5, 6))
+}
+
+#[test]
+fn into_iter() {
+    let stream = three_points();
+    let points = Deserializer::new(stream.bytes.as_slice())
+        .into_iter::<Point>()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(points, vec![Point { x: 1, y: 2 }, Point { x: -3, y: 4 }, Point { x: 5, y: 6 }]);
+}
+
+#[test]
+fn offsets() {
+    let stream = three_points();
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+    let mut offsets = Vec::new();
+
+    {
+        let mut iter = de.iter::<Point>();
+        while let Some(point) = iter.next() {
+            point.unwrap();
+            offsets.push((iter.item_offset(), iter.byte_offset()));
+        }
+    }
+
+    // Each value message takes 8 bytes, the first item includes the type definition
+    let len = stream.bytes.len() as u64;
+    assert_eq!(offsets, vec![(0, len - 16), (len - 16, len - 8), (len - 8, len)]);
+    assert_eq!(de.offset(), len);
+}
+
+#[test]
+fn truncated() {
+    let mut stream = three_points();
+    let len = stream.bytes.len();
+    stream.bytes.truncate(len - 3);
+
+    let mut iter = Deserializer::new(stream.bytes.as_slice()).into_iter::<Point>();
+    assert!(iter.next().unwrap().is_ok());
+    assert!(iter.next().unwrap().is_ok());
+    match *iter.next().unwrap().unwrap_err().kind() {
+        ErrorKind::Io(_) => {},
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+    assert!(iter.next().is_none());
+}
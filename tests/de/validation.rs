@@ -0,0 +1,167 @@
+use gob::{Deserializer,ErrorKind,Corruption};
+use utils::wire::{self,Stream};
+
+#[derive(Deserialize,Debug,PartialEq)]
+struct Inner {
+    #[serde(rename="X")] x: i64,
+}
+
+#[derive(Deserialize,Debug,PartialEq)]
+struct Outer {
+    #[serde(rename="Inner")] inner: Inner,
+}
+
+#[test]
+fn forward_declared_reference() {
+    let mut value = Vec::new();
+    wire::uint(&mut value, 1);
+    wire::uint(&mut value, 1);
+    wire::int(&mut value, 5);
+    wire::uint(&mut value, 0);
+    wire::uint(&mut value, 0);
+
+    let stream = Stream::new()
+        .structure(65, "Outer", &[("Inner", 66)])
+        .structure(66, "Inner", &[("X", 2)])
+        .value(65, &value);
+
+    let outer: Outer = Deserializer::new(stream.bytes.as_slice()).deserialize().unwrap();
+    assert_eq!(outer, Outer { inner: Inner { x: 5 } });
+}
+
+#[test]
+fn large_type_ids() {
+    let mut value = Vec::new();
+    wire::uint(&mut value, 1);
+    wire::uint(&mut value, 1);
+    wire::int(&mut value, 5);
+    wire::uint(&mut value, 0);
+    wire::uint(&mut value, 0);
+
+    let stream = Stream::new()
+        .structure(1 << 40, "Outer", &[("Inner", 65)])
+        .structure(65, "Inner", &[("X", 2)])
+        .value(1 << 40, &value);
+
+    let outer: Outer = Deserializer::new(stream.bytes.as_slice()).deserialize().unwrap();
+    assert_eq!(outer, Outer { inner: Inner { x: 5 } });
+}
+
+#[test]
+fn dangling_field_reference() {
+    let stream = Stream::new()
+        .structure(65, "Outer", &[("Inner", 70)])
+        .value(65, &[0]);
+
+    let err = Deserializer::new(stream.bytes.as_slice()).deserialize::<Outer>().unwrap_err();
+    match *err.kind() {
+        ErrorKind::Corrupt(Corruption::DanglingTypeReference { ref type_name, ref field, type_id: 70 }) => {
+            assert_eq!(type_name, "Outer");
+            assert_eq!(field, "Inner");
+        }
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn dangling_elem_reference() {
+    let streams = vec![
+        Stream::new().slice(65, "[]Foo", 66),
+        Stream::new().array(65, "[3]Foo", 66, 3),
+    ];
+
+    for stream in streams {
+        let stream = stream.value(65, &[0, 0]);
+        let err = Deserializer::new(stream.bytes.as_slice()).deserialize::<Vec<i64>>().unwrap_err();
+        match *err.kind() {
+            ErrorKind::Corrupt(Corruption::DanglingTypeReference { ref field, type_id: 66, .. }) => assert_eq!(field, "Elem"),
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+    }
+}
+
+#[test]
+fn reserved_reference() {
+    let stream = Stream::new()
+        .map(65, "map[int]Foo", 2, 30)
+        .value(65, &[0, 0]);
+
+    let err = Deserializer::new(stream.bytes.as_slice()).deserialize::<Outer>().unwrap_err();
+    match *err.kind() {
+        ErrorKind::Corrupt(Corruption::DanglingTypeReference { ref field, type_id: 30, .. }) => assert_eq!(field, "Elem"),
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn defining_reserved() {
+    let stream = Stream::new()
+        .structure(30, "Outer", &[]);
+
+    let err = Deserializer::new(stream.bytes.as_slice()).deserialize::<Outer>().unwrap_err();
+    match *err.kind() {
+        ErrorKind::Corrupt(Corruption::DefiningReserved { ref type_name, type_id: 30 }) => assert_eq!(type_name, "Outer"),
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn failed_batch_is_undefined() {
+    let stream = Stream::new()
+        .structure(65, "Inner", &[("X", 2)])
+        .structure(2, "Bad", &[])
+        .structure(66, "Outer", &[("Inner", 65)])
+        .value(66, &[0]);
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+
+    let err = de.deserialize::<Outer>().unwrap_err();
+    match *err.kind() {
+        ErrorKind::Corrupt(Corruption::DefiningBuiltin(2)) => {},
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+    assert!(de.types().is_empty());
+
+    // The next batch can't refer to the type defined before the error
+    let err = de.deserialize::<Outer>().unwrap_err();
+    match *err.kind() {
+        ErrorKind::Corrupt(Corruption::DanglingTypeReference { type_id: 65, .. }) => {},
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn length_past_message_end() {
+    let stream = Stream::new()
+        .value(6, &[0, 5, b'a'])
+        .value(6, &[0, 1, b'b']);
+
+    let err = Deserializer::new(stream.bytes.as_slice()).deserialize::<String>().unwrap_err();
+    match *err.kind() {
+        ErrorKind::Corrupt(Corruption::LengthPastMessageEnd(5)) => {},
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn elements_past_message_end() {
+    let stream = Stream::new()
+        .slice(65, "[]int", 2)
+        .value(65, &[0, 3, 2]);
+
+    let err = Deserializer::new(stream.bytes.as_slice()).deserialize::<Vec<i64>>().unwrap_err();
+    match *err.kind() {
+        ErrorKind::Corrupt(Corruption::LengthPastMessageEnd(3)) => {},
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn message_longer_than_value() {
+    let stream = Stream::new().value(2, &[0, 2, 0]);
+
+    let err = Deserializer::new(stream.bytes.as_slice()).deserialize::<i64>().unwrap_err();
+    match *err.kind() {
+        ErrorKind::Corrupt(Corruption::MessageLength { expected_end, end }) => assert_eq!(expected_end, end + 1),
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
@@ -0,0 +1,310 @@
+use gob::{Deserializer,DeserializerOptions,ErrorKind,Corruption,Interface,Value};
+use utils::wire::{self,Stream,points};
+
+/// An `int` held by an interface value registered as `name`
+fn interface_int(out: &mut Vec<u8>, name: &str, n: i64) {
+    let mut value = vec![0];
+    wire::int(&mut value, n);
+
+    wire::string(out, name);
+    wire::int(out, 2);
+    wire::message(out, &value);
+}
+
+fn record_stream() -> Stream {
+    let mut record = Vec::new();
+    wire::uint(&mut record, 1);
+    wire::string(&mut record, "gopher");
+    wire::uint(&mut record, 1);
+    wire::int(&mut record, -3);
+    wire::uint(&mut record, 1);
+    wire::uint(&mut record, 3);
+    wire::uint(&mut record, 1);
+    wire::uint(&mut record, 2);
+    wire::int(&mut record, 1);
+    wire::int(&mut record, 2);
+    wire::uint(&mut record, 1);
+    wire::uint(&mut record, 1);
+    wire::string(&mut record, "a");
+    wire::uint(&mut record, 1);
+    wire::uint(&mut record, 1);
+    wire::string(&mut record, "k");
+    wire::uint(&mut record, 9);
+    wire::uint(&mut record, 1);
+    record.extend(&[0xfe, 0xf0, 0x3f, 0]);
+    wire::uint(&mut record, 1);
+    interface_int(&mut record, "int", 7);
+    wire::uint(&mut record, 0);
+
+    Stream::new()
+        .array(65, "[2]int", 2, 2)
+        .slice(66, "[]string", 6)
+        .map(67, "map[string]uint", 6, 3)
+        .structure(68, "main.Record", &[
+            ("Name", 6), ("Delta", 2), ("Count", 3), ("Pair", 65),
+            ("Tags", 66), ("Scores", 67), ("Z", 7), ("Any", 8),
+        ])
+        .value(68, &record)
+}
+
+#[test]
+fn all_kinds() {
+    let stream = record_stream();
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+    let value: Value = de.deserialize().unwrap();
+
+    let string = |s: &str| Value::String(s.into());
+    let expected = Value::Struct {
+        type_name: "main.Record".into(),
+        fields: vec![
+            ("Name".into(), string("gopher")),
+            ("Delta".into(), Value::Int(-3)),
+            ("Count".into(), Value::Uint(3)),
+            ("Pair".into(), Value::Array {
+                type_name: "[2]int".into(),
+                elems: vec![Value::Int(1), Value::Int(2)],
+            }),
+            ("Tags".into(), Value::Slice { type_name: "[]string".into(), elems: vec![string("a")] }),
+            ("Scores".into(), Value::Map {
+                type_name: "map[string]uint".into(),
+                entries: vec![(string("k"), Value::Uint(9))],
+            }),
+            ("Z".into(), Value::Complex(1.0, 0.0)),
+            ("Any".into(), Value::Interface(Some(Box::new(Interface {
+                name: "int".into(),
+                value: Value::Int(7),
+            })))),
+        ],
+    };
+    assert_eq!(value, expected);
+    assert_eq!(value.type_name(), "main.Record");
+}
+
+#[test]
+fn top_level_builtin() {
+    let mut value = vec![0];
+    wire::uint(&mut value, 5);
+
+    let stream = Stream::new().value(3, &value);
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+    assert_eq!(de.deserialize::<Value>().unwrap(), Value::Uint(5));
+}
+
+#[derive(Deserialize,Debug)]
+struct Envelope {
+    #[serde(rename="Topic")] topic: String,
+    #[serde(rename="Payload")] payload: Value,
+}
+
+#[test]
+fn field_of_typed_struct() {
+    let mut envelope = Vec::new();
+    wire::uint(&mut envelope, 1);
+    wire::string(&mut envelope, "nil");
+    wire::uint(&mut envelope, 1);
+    wire::uint(&mut envelope, 0);
+    wire::uint(&mut envelope, 0);
+
+    let stream = Stream::new()
+        .structure(65, "Envelope", &[("Topic", 6), ("Payload", 8)])
+        .value(65, &envelope);
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+
+    let envelope: Envelope = de.deserialize().unwrap();
+    assert_eq!(envelope.topic, "nil");
+    assert_eq!(envelope.payload, Value::Interface(None));
+    assert_eq!(envelope.payload.type_name(), "interface {}");
+}
+
+#[test]
+fn other_formats() {
+    let value: Value = ::serde_json::from_str(r#"{"a": [1, -2, 1.5, "x", null, true]}"#).unwrap();
+
+    assert_eq!(value, Value::Map {
+        type_name: "map[interface {}]interface {}".into(),
+        entries: vec![(Value::String("a".into()), Value::Slice {
+            type_name: "[]interface {}".into(),
+            elems: vec![
+                Value::Uint(1),
+                Value::Int(-2),
+                Value::Float(1.5),
+                Value::String("x".into()),
+                Value::Interface(None),
+                Value::Bool(true),
+            ],
+        })],
+    });
+}
+
+#[test]
+fn buffered_by_serde() {
+    #[derive(Deserialize,Debug,PartialEq)]
+    #[serde(untagged)]
+    enum Payload {
+        Number(i64),
+        Other(Value),
+    }
+
+    let stream = points();
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+
+    // Go's types are lost, but not the data
+    assert_eq!(de.deserialize::<Payload>().unwrap(), Payload::Other(Value::Map {
+        type_name: "map[interface {}]interface {}".into(),
+        entries: vec![
+            (Value::String("X".into()), Value::Int(1)),
+            (Value::String("Y".into()), Value::Int(2)),
+        ],
+    }));
+}
+
+#[test]
+fn short_array() {
+    let stream = Stream::new()
+        .array(65, "[3]int", 2, 3)
+        .value(65, &[0, 2, 2, 4]);
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+
+    let err = de.deserialize::<Value>().unwrap_err();
+    match *err.kind() {
+        ErrorKind::Corrupt(Corruption::ArrayLength { ref type_name, expected: 3, len: 2 }) => assert_eq!(type_name, "[3]int"),
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn interface_length_mismatch() {
+    let mut value = vec![0];
+    wire::string(&mut value, "int");
+    wire::int(&mut value, 2);
+    wire::message(&mut value, &[0, 14, 0]);
+
+    let stream = Stream::new().value(8, &value);
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+
+    let err = de.deserialize::<Value>().unwrap_err();
+    match *err.kind() {
+        ErrorKind::Corrupt(Corruption::InterfaceLength(3)) => {},
+        ref kind => panic!("unexpected error: {:?}", kind),
+    }
+}
+
+#[test]
+fn decoded_with_options() {
+    // More fields than the default limit allows
+    let names = (0..1100).map(|i| format!("F{}", i)).collect::<Vec<_>>();
+    let fields = names.iter().map(|name| (name.as_str(), 2)).collect::<Vec<_>>();
+
+    let mut envelope = Vec::new();
+    wire::uint(&mut envelope, 1);
+    wire::string(&mut envelope, "wide");
+    wire::uint(&mut envelope, 1);
+    wire::uint(&mut envelope, 1);
+    wire::int(&mut envelope, 1);
+    wire::uint(&mut envelope, 1099);
+    wire::int(&mut envelope, 2);
+    wire::uint(&mut envelope, 0);
+    wire::uint(&mut envelope, 0);
+
+    let stream = Stream::new()
+        .structure(65, "Wide", &fields)
+        .structure(66, "Envelope", &[("Topic", 6), ("Payload", 65)])
+        .value(66, &envelope);
+
+    let options = DeserializerOptions::new().max_fields(2000);
+    let mut de = Deserializer::with_options(stream.bytes.as_slice(), options);
+
+    let envelope: Envelope = de.deserialize().unwrap();
+    assert_eq!(envelope.payload, Value::Struct {
+        type_name: "Wide".into(),
+        fields: vec![("F0".into(), Value::Int(1)), ("F1099".into(), Value::Int(2))],
+    });
+}
+
+fn decode_record() -> Value {
+    let stream = record_stream();
+    let mut de = Deserializer::new(stream.bytes.as_slice());
+    de.deserialize().unwrap()
+}
+
+#[test]
+fn lookup() {
+    let record = decode_record();
+
+    assert_eq!(record["Name"], Value::String("gopher".into()));
+    assert_eq!(record["Pair"][1], Value::Int(2));
+    assert_eq!(record.get("Missing"), None);
+    assert_eq!(record["Pair"].get(2), None);
+
+    assert_eq!(record.pointer(""), Some(&record));
+    assert_eq!(record.pointer("/Tags/0"), Some(&Value::String("a".into())));
+    assert_eq!(record.pointer("/Scores/k"), Some(&Value::Uint(9)));
+    // Interface values are looked through
+    assert_eq!(record.pointer("/Any"), record.get("Any"));
+    assert_eq!(record.pointer("/Tags/00"), None);
+    assert_eq!(record.pointer("Tags"), None);
+}
+
+#[test]
+fn lookup_map_keys() {
+    let entries = vec![
+        (Value::Int(-1), Value::String("negative".into())),
+        (Value::Int(3), Value::String("three".into())),
+    ];
+    let map = Value::Map { type_name: "map[int]string".into(), entries };
+
+    assert_eq!(map[3], Value::String("three".into()));
+    assert_eq!(map.pointer("/-1"), Some(&Value::String("negative".into())));
+    assert_eq!(map.get("x"), None);
+}
+
+#[test]
+#[should_panic(expected = "no such element in value of type main.Record")]
+fn index_missing_field() {
+    let _ = &decode_record()["Missing"];
+}
+
+#[test]
+fn go_formatting() {
+    let record = decode_record();
+
+    assert_eq!(
+        record.to_string(),
+        "{Name:gopher Delta:-3 Count:3 Pair:[1 2] Tags:[a] Scores:map[k:9] Z:(1+0i) Any:7}"
+    );
+    assert_eq!(
+        format!("{:#}", record),
+        r#"main.Record{Name:"gopher", Delta:-3, Count:0x3, Pair:[2]int{1, 2}, Tags:[]string{"a"}, Scores:map[string]uint{"k":0x9}, Z:(1+0i), Any:7}"#
+    );
+}
+
+#[test]
+fn go_formatting_of_scalars() {
+    let float = |x: f64| Value::Float(x).to_string();
+    assert_eq!(float(1.0), "1");
+    assert_eq!(float(0.5), "0.5");
+    assert_eq!(float(123456.0), "123456");
+    assert_eq!(float(1e6), "1e+06");
+    assert_eq!(float(-1.5e-7), "-1.5e-07");
+    assert_eq!(float(f64::INFINITY), "+Inf");
+    assert_eq!(Value::Complex(1.5, -2.0).to_string(), "(1.5-2i)");
+
+    assert_eq!(Value::Bytes(b"hi".to_vec()).to_string(), "[104 105]");
+    assert_eq!(format!("{:#}", Value::Bytes(b"hi".to_vec())), "[]byte{0x68, 0x69}");
+    assert_eq!(format!("{:#}", Value::String("a\"\n\x01".into())), r#""a\"\n\x01""#);
+
+    assert_eq!(Value::Interface(None).to_string(), "<nil>");
+    assert_eq!(format!("{:#}", Value::Interface(None)), "interface {}(nil)");
+}
+
+#[test]
+fn go_formatting_sorts_map_keys() {
+    let entries = vec![
+        (Value::String("b".into()), Value::Bool(true)),
+        (Value::String("a".into()), Value::Bool(false)),
+    ];
+    let map = Value::Map { type_name: "map[string]bool".into(), entries };
+
+    assert_eq!(map.to_string(), "map[a:false b:true]");
+    assert_eq!(format!("{:#}", map), r#"map[string]bool{"a":false, "b":true}"#);
+}
@@ -1,9 +1,20 @@
 #[macro_use] extern crate pretty_assertions;
 #[macro_use] extern crate serde_derive;
+#[cfg(feature = "codec")]
+extern crate bytes;
 extern crate env_logger;
+#[cfg(feature = "codec")]
+extern crate futures;
 extern crate gob;
+#[cfg(feature = "memmap")]
+extern crate memmap2;
 extern crate serde;
 extern crate serde_bytes;
+extern crate serde_json;
+#[cfg(feature = "tokio")]
+extern crate tokio;
+#[cfg(feature = "codec")]
+extern crate tokio_util;
 
 #[macro_use]
 mod utils;
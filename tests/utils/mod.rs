@@ -2,3 +2,4 @@
 #[macro_use]
 pub mod macros;
 pub mod go;
+pub mod wire;
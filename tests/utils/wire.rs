@@ -0,0 +1,143 @@
+//! Minimal gob encoder for building test streams by hand,
+//! for cases that can't be produced by running Go code.
+
+pub fn uint(out: &mut Vec<u8>, n: u64) {
+    if n < 128 {
+        out.push(n as u8);
+        return;
+    }
+
+    let bytes: Vec<u8> = (0..8).rev().map(|i| (n >> (i * 8)) as u8).skip_while(|&b| b == 0).collect();
+    out.push((-(bytes.len() as i8)) as u8);
+    out.extend(bytes);
+}
+
+pub fn int(out: &mut Vec<u8>, n: i64) {
+    let n = if n < 0 { (!n as u64) << 1 | 1 } else { (n as u64) << 1 };
+    uint(out, n);
+}
+
+pub fn string(out: &mut Vec<u8>, s: &str) {
+    uint(out, s.len() as u64);
+    out.extend(s.as_bytes());
+}
+
+/// Appends a length prefixed message
+pub fn message(out: &mut Vec<u8>, body: &[u8]) {
+    uint(out, body.len() as u64);
+    out.extend(body);
+}
+
+/// Appends `CommonType` as the first field of a type definition
+fn common_type(out: &mut Vec<u8>, name: &str, id: i64) {
+    uint(out, 1);
+    uint(out, 1);
+    string(out, name);
+    uint(out, 1);
+    int(out, id);
+    uint(out, 0);
+}
+
+/// Builds a stream of type definitions and values
+#[derive(Default)]
+pub struct Stream {
+    pub bytes: Vec<u8>,
+}
+
+impl Stream {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn define(mut self, id: i64, wire_field: u64, wire_type: Vec<u8>) -> Self {
+        let mut body = Vec::new();
+        int(&mut body, -id);
+        uint(&mut body, wire_field + 1);
+        body.extend(wire_type);
+        uint(&mut body, 0);
+        message(&mut self.bytes, &body);
+        self
+    }
+
+    pub fn array(self, id: i64, name: &str, elem: i64, len: i64) -> Self {
+        let mut t = Vec::new();
+        common_type(&mut t, name, id);
+        uint(&mut t, 1);
+        int(&mut t, elem);
+        uint(&mut t, 1);
+        int(&mut t, len);
+        uint(&mut t, 0);
+        self.define(id, 0, t)
+    }
+
+    pub fn slice(self, id: i64, name: &str, elem: i64) -> Self {
+        let mut t = Vec::new();
+        common_type(&mut t, name, id);
+        uint(&mut t, 1);
+        int(&mut t, elem);
+        uint(&mut t, 0);
+        self.define(id, 1, t)
+    }
+
+    pub fn structure(self, id: i64, name: &str, fields: &[(&str, i64)]) -> Self {
+        let mut t = Vec::new();
+        common_type(&mut t, name, id);
+        uint(&mut t, 1);
+        uint(&mut t, fields.len() as u64);
+        for &(name, id) in fields {
+            uint(&mut t, 1);
+            string(&mut t, name);
+            uint(&mut t, 1);
+            int(&mut t, id);
+            uint(&mut t, 0);
+        }
+        uint(&mut t, 0);
+        self.define(id, 2, t)
+    }
+
+    pub fn map(self, id: i64, name: &str, key: i64, elem: i64) -> Self {
+        let mut t = Vec::new();
+        common_type(&mut t, name, id);
+        uint(&mut t, 1);
+        int(&mut t, key);
+        uint(&mut t, 1);
+        int(&mut t, elem);
+        uint(&mut t, 0);
+        self.define(id, 3, t)
+    }
+
+    /// Appends a value message of the given type, `value` being its encoded body
+    pub fn value(mut self, id: i64, value: &[u8]) -> Self {
+        let mut body = Vec::new();
+        int(&mut body, id);
+        body.extend(value);
+        message(&mut self.bytes, &body);
+        self
+    }
+}
+
+/// Go's `struct { X, Y int }`, the type most tests decode
+#[derive(Deserialize,Debug,PartialEq)]
+pub struct Point {
+    #[serde(rename="X")] pub x: i64,
+    #[serde(rename="Y")] pub y: i64,
+}
+
+/// Encodes the fields of a `Point`
+pub fn point(x: i64, y: i64) -> Vec<u8> {
+    let mut value = Vec::new();
+    uint(&mut value, 1);
+    int(&mut value, x);
+    uint(&mut value, 1);
+    int(&mut value, y);
+    uint(&mut value, 0);
+    value
+}
+
+/// Defines `Point` as type 65, followed by two of them
+pub fn points() -> Stream {
+    Stream::new()
+        .structure(65, "Point", &[("X", 2), ("Y", 2)])
+        .value(65, &point(1, 2))
+        .value(65, &point(-3, 4))
+}